@@ -2,6 +2,8 @@ pub mod request;
 pub use request::*;
 pub mod endpoints;
 pub use endpoints::*;
+pub mod queries;
+pub use queries::*;
 
 pub const API_URL: &str = "http://localhost:3000/v1/";
 
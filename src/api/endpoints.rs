@@ -4,7 +4,7 @@ use crate::endpoint;
 use api_types::v1::*;
 use api_types::Snowflake;
 use gloo_net::http::Response;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 
 /// This struct is used to convince the type system to allow us to send_request a None body:
 /// if it were Some, it would have to be a type that implements Serialize.
@@ -22,6 +22,18 @@ impl AsPathFragment for Snowflake {
     }
 }
 
+/// Append the query parameters to a path. Parameters that are `None` are expected to be left out
+/// with `skip_serializing_if`, since the API can't parse empty values.
+fn with_query(path: &str, query: &impl Serialize) -> Result<String, gloo_net::Error> {
+    let query = serde_urlencoded::to_string(query)
+        .map_err(|e| gloo_net::Error::GlooError(format!("Failed to encode query: {e}")))?;
+    if query.is_empty() {
+        Ok(path.to_string())
+    } else {
+        Ok(format!("{path}?{query}"))
+    }
+}
+
 /// This function parses a [`gloo_net::http::Response`] with JSON,
 /// except for if we want to parse into the unit type;
 /// in that case, it just returns the unit type.
@@ -101,6 +113,37 @@ macro_rules! api_request {
             }
         }
     };
+
+    // Request has query parameters: api_request!(get_events: GET "auth/security_events" ?(SecurityEventsQuery) => (200 SecurityEventPage) )
+    ($name:ident : $method:ident $path:literal ?($querytype:ty) => $( ( $status:literal $responsetype:ty ) )+) => {
+        paste::paste!{
+            #[derive(Debug, Clone)]
+            #[allow(non_camel_case_types)]
+            pub enum [<ResponseType_ $name>] {
+                $(
+                    [<Status $status>]($responsetype),
+                )*
+            }
+            pub async fn $name(query: $querytype) -> Result<[<ResponseType_ $name>], gloo_net::Error> {
+                log::debug!("-> {}({:?})", stringify!($name), query);
+                let url = endpoint!(with_query($path, &query)?);
+                let missing_body: Option<NoBody> = None;
+                let response =
+                    send_request(&url, gloo_net::http::Method::$method, missing_body).await?;
+                let response = match response.status() {
+                    $(
+                        $status => {
+                            let content = parse_json::<$responsetype>(response).await?;
+                            [<ResponseType_ $name>]::[<Status $status>](content)
+                        },
+                    )*
+                    other => return Err(gloo_net::Error::GlooError(format!("In $name, received unexpected status: {}", other)))
+                };
+                log::debug!("<- {response:?}");
+                Ok(response)
+            }
+        }
+    };
 }
 
 macro_rules! ident_as_format_question {
@@ -215,7 +258,8 @@ macro_rules! api_request_with_path {
     };
 }
 api_request!(auth_check: GET "auth/check" => (200 CheckResponse));
-api_request!(auth_login: POST "auth/login" (LoginRequest) => (200 LoginSuccess) (401 LoginError));
+api_request!(auth_login: POST "auth/login" (LoginRequest) => (200 LoginSuccess) (202 SecondFactorChallenge) (401 LoginError));
+api_request_with_path!(auth_login_second_factor: POST "auth/login/second_factor/{}" (id Snowflake) => SecondFactorRequest => (200 LoginSuccess) (401 LoginError) (404 ()));
 api_request!(auth_get_current_token: GET "auth/token/@me" => (200 TokenData));
 api_request!(
     auth_register: POST
@@ -232,7 +276,8 @@ api_request!(registration_get_prerequisites: GET "auth/registration" => (200 Reg
 api_request_with_path!(registration_get: GET "auth/registration/{}" (id Snowflake,) => (200 PendingRegistration) (404 ()));
 api_request_with_path!(registration_confirm: POST "auth/registration/{}/confirm" (id Snowflake) => ConfirmRegistrationRequest => (200 ConfirmRegistrationResponse));
 
-api_request!(sleep_get_list: GET "sleep/list" => (200 Vec<SleepState>) (404 ()));
+api_request!(sleep_get_list: GET "sleep/list" ?(SleepListQuery) => (200 SleepStatePage) (404 ()));
+api_request!(sleep_create: POST "sleep/list" (NewSleepRecord) => (201 SleepState) (409 ()));
 api_request!(sleep_create_new_current: POST "sleep/new" => (201 SleepState) (409 ()));
 
 api_request_with_path!(sleep_get_by_id: GET "sleep/{}" (id Snowflake) => (200 SleepState) (404 ()));
@@ -243,3 +288,35 @@ api_request!(sleep_get_current: GET "sleep/@current" => (200 SleepState) (404 ()
 api_request!(sleep_set_current_start: PUT "sleep/@current" => (204 ()) (404 ()));
 api_request!(sleep_set_current_end: POST "sleep/@current" => (204 ()) (404 ()));
 api_request!(sleep_delete_current: DELETE "sleep/@current" => (204 ()) (404 ()));
+
+api_request!(auth_get_recovery_codes_status: GET "auth/recovery_codes" => (200 RecoveryCodesStatus) (404 ()));
+api_request!(auth_generate_recovery_codes: POST "auth/recovery_codes" => (200 RecoveryCodes) (404 ()));
+
+api_request!(auth_passkey_register_start: POST "auth/passkey/register" => (200 PasskeyChallenge));
+api_request_with_path!(auth_passkey_register_finish: POST "auth/passkey/register/{}" (id Snowflake) => PasskeyRegistration => (201 PasskeyInfo) (400 ()) (404 ()));
+api_request!(auth_passkey_login_start: POST "auth/passkey/login" => (200 PasskeyChallenge));
+api_request_with_path!(auth_passkey_login_finish: POST "auth/passkey/login/{}" (id Snowflake) => PasskeyAssertion => (200 LoginSuccess) (401 LoginError) (404 ()));
+api_request!(auth_get_passkeys: GET "auth/passkey/list" => (200 Vec<PasskeyInfo>));
+api_request_with_path!(auth_rename_passkey: PUT "auth/passkey/by_id/{}" (id Snowflake) => RenamePasskeyRequest => (204 ()) (404 ()));
+api_request_with_path!(auth_delete_passkey: DELETE "auth/passkey/by_id/{}" (id Snowflake) => (204 ()) (404 ()));
+
+api_request!(auth_get_oidc_providers: GET "auth/oidc/providers" => (200 Vec<OidcProvider>));
+api_request!(auth_oidc_callback: POST "auth/oidc/callback" (OidcCallbackRequest) => (200 LoginSuccess) (401 LoginError));
+
+api_request!(user_get_settings: GET "user/settings" => (200 UserSettings));
+api_request!(user_put_settings: PUT "user/settings" (UserSettings) => (204 ()));
+
+api_request!(auth_request_magic_link: POST "auth/magic_link" (MagicLinkRequest) => (204 ()));
+api_request_with_path!(auth_consume_magic_link: POST "auth/magic_link/{}" (id Snowflake) => MagicLinkConfirmRequest => (200 LoginSuccess) (401 LoginError) (404 ()));
+
+api_request!(registration_check_availability: POST "auth/registration/availability" (AvailabilityCheck) => (200 AvailabilityResponse));
+
+api_request_with_path!(registration_resend_confirmation: POST "auth/registration/{}/resend" (id Snowflake) => (204 ()) (404 ()) (429 ()));
+
+api_request!(invite_get_list: GET "auth/invite/list" => (200 Vec<Invite>) (403 ()));
+api_request!(invite_create: POST "auth/invite/list" => (201 Invite) (403 ()));
+api_request_with_path!(invite_revoke: DELETE "auth/invite/by_id/{}" (id Snowflake) => (204 ()) (404 ()));
+
+api_request_with_path!(auth_rename_token: PUT "auth/token/by_id/{}" (id Snowflake) => RenameTokenRequest => (204 ()) (404 ()));
+
+api_request!(auth_get_security_events: GET "auth/security_events" ?(SecurityEventsQuery) => (200 SecurityEventPage));
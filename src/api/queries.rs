@@ -0,0 +1,29 @@
+//! Query parameters of API requests.
+//!
+//! Missing values must be left out entirely rather than sent empty,
+//! because the API can't parse an empty value into an `Option`.
+
+use api_types::{v1::DateTimeUtc, Snowflake};
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SecurityEventsQuery {
+    /// Only return events older than this one, to get the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Snowflake>,
+}
+
+/// Records are sorted by start time, newest first. Sleep records can't overlap,
+/// so no two of them start at the same time and the start time works as a cursor.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SleepListQuery {
+    /// Only return records that started before this time, to get the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<DateTimeUtc>,
+    /// Only return records that ended at or after this time, or are still going on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTimeUtc>,
+    /// Only return records that started before this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTimeUtc>,
+}
@@ -11,4 +11,26 @@ pub use navbar::*;
 pub mod logout_button;
 pub use logout_button::*;
 pub mod session_list;
-pub use session_list::*;
\ No newline at end of file
+pub use session_list::*;
+pub mod recovery_codes;
+pub use recovery_codes::*;
+pub mod passkey_list;
+pub use passkey_list::*;
+pub mod oidc_provider_buttons;
+pub use oidc_provider_buttons::*;
+pub mod session_expiry_warning;
+pub use session_expiry_warning::*;
+pub mod idle_logout;
+pub use idle_logout::*;
+pub mod password_strength_meter;
+pub use password_strength_meter::*;
+pub mod captcha_widget;
+pub use captcha_widget::*;
+pub mod invite_manager;
+pub use invite_manager::*;
+pub mod security_activity;
+pub use security_activity::*;
+pub mod confirm_button;
+pub use confirm_button::*;
+pub mod second_factor_form;
+pub use second_factor_form::*;
\ No newline at end of file
@@ -0,0 +1,86 @@
+use api_types::v1::CaptchaConfig;
+use yew::{platform::spawn_local, prelude::*};
+use yew_bootstrap::{component::Alert, util::Color};
+
+use crate::{
+    components::{LoadingSpinner, Size},
+    utils::captcha,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct CaptchaWidgetProps {
+    /// The captcha service that registration has to go through, as configured on the server.
+    pub config: CaptchaConfig,
+
+    /// Called with the widget ID once the captcha is shown and can be solved.
+    pub on_ready: Callback<String>,
+}
+
+/// Shows the captcha of the given provider, loading its script first.
+///
+/// If the script can't be loaded (e.g. because of an ad blocker), an error is shown instead,
+/// with a button to try again.
+#[function_component(CaptchaWidget)]
+pub fn captcha_widget(props: &CaptchaWidgetProps) -> Html {
+    let container_ref = use_node_ref();
+    let load_error = use_state(|| None::<String>);
+    let is_loading = use_state(|| false);
+    let attempt = use_state(|| 0u32);
+
+    {
+        let container_ref = container_ref.clone();
+        let load_error = load_error.clone();
+        let is_loading = is_loading.clone();
+        let on_ready = props.on_ready.clone();
+        use_effect_with_deps(
+            move |(config, _)| {
+                let config = config.clone();
+                load_error.set(None);
+                is_loading.set(captcha::is_required(&config));
+                spawn_local(async move {
+                    match captcha::render(&config, &container_ref).await {
+                        Ok(widget_id) => on_ready.emit(widget_id),
+                        Err(e) => {
+                            log::error!("Failed to load the captcha: {e}");
+                            load_error.set(Some(e));
+                        }
+                    }
+                    is_loading.set(false);
+                });
+            },
+            (props.config.clone(), *attempt),
+        );
+    }
+
+    match &props.config {
+        CaptchaConfig::None => html! {},
+        CaptchaConfig::Stub => html! {
+            <p class="text-body-secondary small">{"The captcha is stubbed out on this server."}</p>
+        },
+        CaptchaConfig::Hcaptcha { .. } | CaptchaConfig::Turnstile { .. } => {
+            let maybe_error_alert = match &*load_error {
+                Some(_) => {
+                    let onretry = {
+                        let attempt = attempt.clone();
+                        Callback::from(move |_| attempt.set(*attempt + 1))
+                    };
+                    html! {
+                        <Alert style={Color::Danger}>
+                            {format!("{} could not be loaded, so you can't register right now. ", captcha::provider_name(&props.config))}
+                            {"If you use an ad or script blocker, allow it on this page. "}
+                            <button type="button" class="btn btn-sm btn-danger" onclick={onretry}>{"Try again"}</button>
+                        </Alert>
+                    }
+                }
+                None => html! {},
+            };
+            html! {
+                <>
+                    {maybe_error_alert}
+                    <LoadingSpinner show={*is_loading} size={Size::Small} />
+                    <div class="mb-3" ref={container_ref} />
+                </>
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{future::Future, pin::Pin};
+
+use web_sys::HtmlInputElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_bootstrap::component::{Modal, ModalBody, ModalFooter, ModalHeader};
+
+use crate::components::{Color, LoadingSpinner, Size};
+
+#[derive(Properties, PartialEq)]
+pub struct ConfirmButtonProps {
+    /// ID of the modal; must be unique on the page.
+    pub id: AttrValue,
+
+    #[prop_or(Color::Danger)]
+    pub color: Color,
+
+    #[prop_or(Size::Default)]
+    pub size: Size,
+
+    /// Runs once the user has confirmed. Nothing may happen before the returned future is polled,
+    /// so do all the work inside it.
+    pub onclick_fn: Callback<(), Pin<Box<dyn Future<Output = ()>>>>,
+
+    #[prop_or(true)]
+    pub enabled: bool,
+
+    /// Show the spinner even if the action itself is not running, e.g. when it only starts a `use_async`.
+    #[prop_or(false)]
+    pub spinning: bool,
+
+    pub text: AttrValue,
+    pub class: Classes,
+
+    /// Title of the dialog.
+    pub title: AttrValue,
+
+    /// Text of the button in the dialog that runs the action.
+    #[prop_or("Confirm".into())]
+    pub confirm_text: AttrValue,
+
+    /// If set, the user has to type this text before they can confirm.
+    #[prop_or_default]
+    pub type_to_confirm: Option<AttrValue>,
+
+    /// Body of the dialog, explaining what will happen.
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Button that asks for confirmation in a modal before running its action,
+/// for actions that can't be undone.
+#[function_component(ConfirmButton)]
+pub fn confirm_button(props: &ConfirmButtonProps) -> Html {
+    let typed = use_state(String::new);
+    let is_action_running = use_state(|| false);
+
+    let is_confirm_enabled = match &props.type_to_confirm {
+        Some(phrase) => typed.as_str() == phrase.as_str(),
+        None => true,
+    };
+
+    let onconfirm = {
+        let onclick_fn = props.onclick_fn.clone();
+        let is_action_running = is_action_running.clone();
+        let typed = typed.clone();
+        Callback::from(move |_| {
+            typed.set(String::new());
+            is_action_running.set(true);
+            // Only create the action now, so that rendering the button never runs it.
+            let action = onclick_fn.emit(());
+            let is_action_running = is_action_running.clone();
+            spawn_local(async move {
+                action.await;
+                is_action_running.set(false);
+            });
+        })
+    };
+
+    let maybe_type_to_confirm = match &props.type_to_confirm {
+        Some(phrase) => {
+            let oninput = {
+                let typed = typed.clone();
+                Callback::from(move |event: InputEvent| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    typed.set(input.value());
+                })
+            };
+            let input_id = format!("{}-type-to-confirm", props.id);
+            html! {
+                <div class="mt-3">
+                    <label for={input_id.clone()} class="form-label">
+                        {"Type "}<strong class="font-monospace">{phrase}</strong>{" to confirm."}
+                    </label>
+                    <input type="text" class="form-control" id={input_id} autocomplete="off"
+                        value={(*typed).clone()} {oninput} />
+                </div>
+            }
+        }
+        None => html! {},
+    };
+
+    let is_running = *is_action_running || props.spinning;
+    let button_classes = classes!(
+        props.class.clone(),
+        "btn",
+        format!("btn-{}", props.color),
+        props.size.class("btn")
+    );
+
+    html! {
+        <>
+            <button type="button" class={button_classes} disabled={!props.enabled || is_running}
+                data-bs-toggle="modal" data-bs-target={format!("#{}", props.id)}>
+                <LoadingSpinner show={is_running} inline={true} size={Size::Small} />
+                {&props.text}
+            </button>
+            <Modal id={props.id.to_string()}>
+                <ModalHeader title={props.title.to_string()} id={props.id.to_string()} />
+                <ModalBody>
+                    {for props.children.iter()}
+                    {maybe_type_to_confirm}
+                </ModalBody>
+                <ModalFooter>
+                    <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">{"Cancel"}</button>
+                    <button type="button" class={classes!("btn", format!("btn-{}", props.color))} data-bs-dismiss="modal"
+                        disabled={!is_confirm_enabled} onclick={onconfirm}>
+                        {&props.confirm_text}
+                    </button>
+                </ModalFooter>
+            </Modal>
+        </>
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
         sleep_create_new_current, sleep_delete_current, sleep_get_current, sleep_set_current_end,
         sleep_set_current_start,
     },
-    components::{AsyncButton, Button, Color},
+    components::{AsyncButton, Button, Color, ConfirmButton},
     utils::get_current_time,
 };
 
@@ -106,7 +106,10 @@ fn current_sleep_status_inner() -> HtmlResult {
                             <AsyncButton class="btn-success w-100 btn-lg mb-2" text="Wake up" color={Color::Success} onclick_fn={wake_up_fn} />
                             <div class="btn-group btn-sm w-100 mb-2">
                                 <AsyncButton class="" text="Could not fall asleep before" color={Color::Warning} onclick_fn={update_start_fn} />
-                                <AsyncButton class="" text="Did not go to sleep at all" color={Color::Danger} onclick_fn={delete_sleep_fn}/>
+                                <ConfirmButton id="confirm-delete-current-sleep" class="" text="Did not go to sleep at all"
+                                    onclick_fn={delete_sleep_fn} title="Discard this sleep?" confirm_text="Discard">
+                                    <p>{"The sleep you are tracking right now will be deleted, as if you never started it."}</p>
+                                </ConfirmButton>
                             </div>
 
                         </div>
@@ -0,0 +1,257 @@
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use web_sys::HtmlSelectElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::{use_event_with_window, use_interval};
+use yew_router::prelude::use_navigator;
+
+use crate::{
+    api::*,
+    components::AsyncButton,
+    context::UserSettingsContext,
+    utils::{
+        auth_events::{broadcast_auth_event, AuthEvent},
+        get_unix_timestamp,
+    },
+    Route,
+};
+
+/// How long before the idle logout the warning is shown, in seconds.
+const IDLE_WARNING_SECONDS: f64 = 60.0;
+
+/// The timeouts offered in the profile settings, in minutes.
+const IDLE_TIMEOUT_CHOICES: [u32; 5] = [5, 15, 30, 60, 120];
+
+/// Key in `localStorage` with the time of the latest activity in any tab.
+/// Logging out ends the session of every tab, so it only happens once all of them are idle.
+const LAST_ACTIVITY_KEY: &str = "oyasumi-last-activity";
+
+/// How often a tab writes its activity to `localStorage` at most, in seconds.
+const ACTIVITY_SHARE_INTERVAL: f64 = 5.0;
+
+fn now() -> f64 {
+    #[allow(unused_unsafe)]
+    unsafe {
+        get_unix_timestamp()
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+/// The latest activity in any tab, as written by [`share_activity`].
+fn shared_last_activity() -> Option<f64> {
+    local_storage()?
+        .get_item(LAST_ACTIVITY_KEY)
+        .ok()
+        .flatten()?
+        .parse()
+        .ok()
+}
+
+fn share_activity(time: f64) {
+    if let Some(storage) = local_storage() {
+        storage.set_item(LAST_ACTIVITY_KEY, &time.to_string()).ok();
+    }
+}
+
+/// Record the time of every interaction with the page into `last_activity`,
+/// and share it with the other tabs every few seconds.
+#[hook]
+fn use_activity_tracker(last_activity: Rc<RefCell<f64>>) {
+    let last_shared = use_mut_ref(|| 0.0);
+    // Opening the dashboard counts as activity too.
+    use_effect_with_deps(|_| share_activity(now()), ());
+
+    let mark_active = move |_: Event| {
+        let now = now();
+        *last_activity.borrow_mut() = now;
+        if now - *last_shared.borrow() >= ACTIVITY_SHARE_INTERVAL {
+            *last_shared.borrow_mut() = now;
+            share_activity(now);
+        }
+    };
+    use_event_with_window("mousemove", mark_active.clone());
+    use_event_with_window("mousedown", mark_active.clone());
+    use_event_with_window("keydown", mark_active.clone());
+    use_event_with_window("scroll", mark_active.clone());
+    use_event_with_window("touchstart", mark_active);
+}
+
+/// Logs the user out after the inactivity timeout from their settings,
+/// with a warning shortly before.
+///
+/// Does nothing if the user has not turned the timeout on.
+/// It's expected that this is placed inside the dashboard, below the [`UserSettingsContext`] provider.
+#[function_component(IdleLogout)]
+pub fn idle_logout() -> Html {
+    let settings_ctx = use_context::<UserSettingsContext>()
+        .expect("UserSettingsContext not found while rendering IdleLogout");
+    let navigator = use_navigator().unwrap();
+
+    // Kept in a ref so that every mouse movement doesn't re-render.
+    let last_activity = use_mut_ref(now);
+    let seconds_left = use_state(|| None::<i64>);
+    let is_logging_out = use_state(|| false);
+
+    use_activity_tracker(last_activity.clone());
+
+    let timeout_minutes = settings_ctx
+        .settings
+        .as_ref()
+        .and_then(|settings| settings.idle_logout_minutes);
+
+    {
+        let last_activity = last_activity.clone();
+        let seconds_left = seconds_left.clone();
+        let is_logging_out = is_logging_out.clone();
+        use_interval(
+            move || {
+                let timeout_minutes = match timeout_minutes {
+                    Some(timeout_minutes) if !*is_logging_out => timeout_minutes,
+                    _ => return,
+                };
+                // Activity in another tab keeps this one signed in as well.
+                let last_activity = match shared_last_activity() {
+                    Some(shared) => shared.max(*last_activity.borrow()),
+                    None => *last_activity.borrow(),
+                };
+                let idle_for = now() - last_activity;
+                let remaining = f64::from(timeout_minutes) * 60.0 - idle_for;
+
+                if remaining <= 0.0 {
+                    log::info!("Logging out after {timeout_minutes} minutes of inactivity");
+                    is_logging_out.set(true);
+                    let navigator = navigator.clone();
+                    spawn_local(async move {
+                        if auth_logout().await.is_err() {
+                            log::error!("Failed to log out!");
+                        }
+                        broadcast_auth_event(AuthEvent::LoggedOut);
+                        navigator.push(&Route::Login);
+                    });
+                } else if remaining <= IDLE_WARNING_SECONDS {
+                    seconds_left.set(Some(remaining.ceil() as i64));
+                } else if seconds_left.is_some() {
+                    seconds_left.set(None);
+                }
+            },
+            1000,
+        );
+    }
+
+    let seconds_left = match *seconds_left {
+        Some(seconds_left) => seconds_left,
+        None => return html! {},
+    };
+
+    html! {
+        <div class="modal d-block" tabindex="-1" role="dialog" style="background-color: rgba(0, 0, 0, 0.5);">
+            <div class="modal-dialog modal-dialog-centered" role="document">
+                <div class="modal-content">
+                    <div class="modal-header">
+                        <h5 class="modal-title">{"Are you still there?"}</h5>
+                    </div>
+                    <div class="modal-body">
+                        <p>{format!("You will be logged out in {seconds_left} seconds because of inactivity.")}</p>
+                        <p>{"Move the mouse or press any key to stay signed in."}</p>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Profile setting for the inactivity timeout used by [`IdleLogout`].
+#[function_component(IdleLogoutSettings)]
+pub fn idle_logout_settings() -> Html {
+    let settings_ctx = use_context::<UserSettingsContext>()
+        .expect("UserSettingsContext not found while rendering IdleLogoutSettings");
+    let selected = use_state(|| None::<Option<u32>>);
+    let is_save_error = use_state(|| false);
+
+    let settings = match &settings_ctx.settings {
+        Some(settings) => settings.clone(),
+        None => {
+            return html! {
+                <>
+                <h2>{ "Automatic logout" }</h2>
+                <p class="placeholder-glow"><span class="placeholder col-6"></span></p>
+                </>
+            }
+        }
+    };
+    let current = (*selected).unwrap_or(settings.idle_logout_minutes);
+
+    let onchange = {
+        let selected = selected.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            selected.set(Some(select.value().parse().ok()));
+        })
+    };
+
+    let save_fn = {
+        let settings_ctx = settings_ctx.clone();
+        let is_save_error = is_save_error.clone();
+        let selected = selected.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let settings_ctx = settings_ctx.clone();
+            let is_save_error = is_save_error.clone();
+            let selected = selected.clone();
+            let mut settings = settings.clone();
+            settings.idle_logout_minutes = current;
+            Box::pin(async move {
+                match user_put_settings(settings.clone()).await {
+                    Ok(ResponseType_user_put_settings::Status204(_)) => {
+                        is_save_error.set(false);
+                        selected.set(None);
+                        settings_ctx.set.emit(settings);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to save settings: {e}");
+                        is_save_error.set(true);
+                    }
+                }
+            })
+        })
+    };
+
+    let options = IDLE_TIMEOUT_CHOICES
+        .iter()
+        .map(|minutes| {
+            html! {
+                <option value={minutes.to_string()} selected={current == Some(*minutes)}>
+                    {format!("After {minutes} minutes of inactivity")}
+                </option>
+            }
+        })
+        .collect::<Html>();
+
+    let maybe_error_alert = if *is_save_error {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to save your setting. Try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <>
+        <h2>{ "Automatic logout" }</h2>
+        <p>
+            { "On a shared computer, you can be logged out automatically when you stop using the dashboard." }
+        </p>
+        {maybe_error_alert}
+        <div class="input-group mb-3">
+            <select class="form-select" {onchange}>
+                <option value="" selected={current.is_none()}>{"Never"}</option>
+                {options}
+            </select>
+            <AsyncButton class="" text="Save" color={crate::components::Color::Primary} onclick_fn={save_fn} enabled={selected.is_some()} />
+        </div>
+        </>
+    }
+}
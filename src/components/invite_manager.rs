@@ -0,0 +1,179 @@
+use std::{future::Future, pin::Pin};
+
+use api_types::v1::Invite;
+use yew::{prelude::*, suspense::use_future_with_deps};
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_router::Routable;
+
+use crate::{api::*, components::AsyncButton, Route};
+
+/// The full URL that registers with the given invite code.
+fn invite_link(code: &str) -> String {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    let path = Route::RegisterWithInvite {
+        code: code.to_string(),
+    }
+    .to_path();
+    format!("{origin}{path}")
+}
+
+/// Lets the user create invite links for new accounts, and revoke the unused ones.
+///
+/// Renders nothing if the user is not allowed to invite people.
+#[function_component(InviteManager)]
+pub fn invite_manager() -> Html {
+    html! {
+        <Suspense fallback={html!()}>
+            <InviteManagerInner />
+        </Suspense>
+    }
+}
+
+#[function_component(InviteManagerInner)]
+fn invite_manager_inner() -> HtmlResult {
+    let refresh_pulse = use_state(|| false);
+    let refresh_pulse_out = refresh_pulse.clone();
+
+    let invites = use_future_with_deps(
+        |_refresh_pulse_state| async { invite_get_list().await },
+        refresh_pulse_out,
+    );
+    let invites = invites?;
+
+    let is_create_error = use_state(|| false);
+
+    let on_change = {
+        let refresh_pulse = refresh_pulse.clone();
+        Callback::from(move |_| refresh_pulse.set(!*refresh_pulse))
+    };
+
+    let create_fn = {
+        let is_create_error = is_create_error.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let is_create_error = is_create_error.clone();
+            let on_change = on_change.clone();
+            Box::pin(async move {
+                match invite_create().await {
+                    Ok(ResponseType_invite_create::Status201(invite)) => {
+                        log::info!("Created invite: {}", invite.id);
+                        is_create_error.set(false);
+                        on_change.emit(());
+                    }
+                    _ => {
+                        log::error!("Failed to create invite");
+                        is_create_error.set(true);
+                    }
+                }
+            })
+        })
+    };
+
+    let result_html = match &*invites {
+        Ok(ResponseType_invite_get_list::Status200(invites)) => {
+            let invite_rows = if invites.is_empty() {
+                html! { <p>{"You have not created any invites yet."}</p> }
+            } else {
+                invites
+                    .iter()
+                    .map(|invite| {
+                        html! {
+                            <InviteRow key={invite.id.to_string()} invite={invite.clone()} on_change={on_change.clone()} />
+                        }
+                    })
+                    .collect::<Html>()
+            };
+
+            let maybe_error_alert = if *is_create_error {
+                html! {
+                    <Alert style={Color::Danger}>{"Failed to create an invite. Try again."}</Alert>
+                }
+            } else {
+                html! {}
+            };
+
+            html! {
+                <>
+                    <h2>{ "Invites" }</h2>
+                    <p>
+                        { "New accounts on this server need an invite. Each invite link can be used to register one account." }
+                    </p>
+                    {maybe_error_alert}
+                    <AsyncButton class="mb-3" text="Create an invite" color={crate::components::Color::Primary} onclick_fn={create_fn} />
+                    {invite_rows}
+                </>
+            }
+        }
+        // Not allowed to invite anyone, so there is nothing to manage.
+        Ok(ResponseType_invite_get_list::Status403(_)) => html! {},
+        Err(_) => {
+            html! {
+                <>
+                    <h2>{ "Invites" }</h2>
+                    <Alert style={Color::Danger}>{"Failed to load your invites. Try reloading the page."}</Alert>
+                </>
+            }
+        }
+    };
+
+    Ok(result_html)
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct InviteRowProps {
+    invite: Invite,
+    on_change: Callback<()>,
+}
+
+#[function_component(InviteRow)]
+fn invite_row(props: &InviteRowProps) -> Html {
+    let invite_id = props.invite.id;
+
+    let revoke_fn = {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let on_change = on_change.clone();
+            Box::pin(async move {
+                match invite_revoke(invite_id).await {
+                    // If it is already missing, it's revoked
+                    Ok(_) => on_change.emit(()),
+                    Err(_) => log::error!("Failed to revoke invite: {invite_id}"),
+                }
+            })
+        })
+    };
+
+    let expires = match props.invite.expires {
+        Some(expires) => format!("Expires at: {expires}"),
+        None => "Never expires".to_string(),
+    };
+
+    let (status, actions) = match &props.invite.used_by {
+        Some(username) => (format!("Used by {username}"), html! {}),
+        None => (
+            "Not used yet".to_string(),
+            html! {
+                <AsyncButton class="" text="Revoke" color={crate::components::Color::Danger} onclick_fn={revoke_fn} />
+            },
+        ),
+    };
+
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                <h5 class="card-title font-monospace">{&props.invite.code}</h5>
+                if props.invite.used_by.is_none() {
+                    <input type="text" class="form-control mb-2" readonly={true} value={invite_link(&props.invite.code)} />
+                }
+                <p class="card-text">
+                    {format!("Created at: {}", props.invite.created)}<br />
+                    {expires}<br />
+                    {status}
+                </p>
+                {actions}
+            </div>
+        </div>
+    }
+}
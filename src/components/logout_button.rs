@@ -5,6 +5,7 @@ use yew_router::prelude::use_navigator;
 use crate::{
     api::auth_logout,
     components::{LoadingSpinner, Size},
+    utils::auth_events::{broadcast_auth_event, AuthEvent},
     Route,
 };
 
@@ -28,6 +29,7 @@ pub fn logout_button(props: &LogoutButtonProps) -> Html {
             match response {
                 Ok(crate::api::ResponseType_auth_logout::Status204(_)) => {
                     log::info!("Logged out!");
+                    broadcast_auth_event(AuthEvent::LoggedOut);
                     navigator.push(&Route::Login);
                 }
                 _ => {
@@ -7,7 +7,7 @@ use yew_router::prelude::Link;
 use crate::{components::LogoutButton, context::UserContext, Route};
 
 /// A component that displays a user menu as a dropdown.
-/// The menu contains a link to the user's profile, and a logout button.
+/// The menu contains the name of the current session if it has one, a link to the user's profile, and a logout button.
 #[function_component(UserMenu)]
 pub fn user_menu() -> Html {
     let user_ctx = use_context::<Rc<UserContext>>();
@@ -19,12 +19,15 @@ pub fn user_menu() -> Html {
 
     match user_ctx.as_ref() {
         UserContext::LoggedOut => html! {},
-        UserContext::LoggedIn(TokenData { user, token: _ }) => html! {
+        UserContext::LoggedIn(TokenData { user, token }) => html! {
             <div class="dropdown">
                 <button class="btn btn-dark dropdown-toggle" data-bs-display="static" data-bs-toggle="dropdown" aria-expanded="false">
                     {&user.username}
                 </button>
                 <ul class="dropdown-menu dropdown-menu-end">
+                    if let Some(name) = &token.name {
+                        <li><h6 class="dropdown-header">{format!("This session: {name}")}</h6></li>
+                    }
                     <li><Link<Route> classes="dropdown-item" to={Route::DashboardProfile}>{"Profile"}</Link<Route>></li>
                     <li><LogoutButton class="dropdown-item" /></li>
                 </ul>
@@ -0,0 +1,97 @@
+use api_types::v1::OidcProvider;
+use yew::{prelude::*, suspense::use_future};
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::use_async;
+
+use crate::{
+    api::*,
+    components::{LoadingSpinner, Size},
+    utils::oidc::start_login,
+};
+
+/// Buttons for signing in through each external OpenID Connect provider that the API is configured with.
+///
+/// Renders nothing if there are no providers, or if they could not be loaded.
+#[function_component(OidcProviderButtons)]
+pub fn oidc_provider_buttons() -> Html {
+    html! {
+        <Suspense fallback={html!()}>
+            <OidcProviderButtonsInner />
+        </Suspense>
+    }
+}
+
+#[function_component(OidcProviderButtonsInner)]
+fn oidc_provider_buttons_inner() -> HtmlResult {
+    let providers = use_future(|| async { auth_get_oidc_providers().await })?;
+
+    let result_html = match &*providers {
+        Ok(ResponseType_auth_get_oidc_providers::Status200(providers)) if !providers.is_empty() => {
+            let buttons = providers
+                .iter()
+                .map(|provider| {
+                    html! { <OidcProviderButton key={provider.id.clone()} provider={provider.clone()} /> }
+                })
+                .collect::<Html>();
+            html! {
+                <>
+                    <p class="text-center text-body-secondary mb-2">{"or continue with"}</p>
+                    {buttons}
+                </>
+            }
+        }
+        Ok(_) => html! {},
+        Err(e) => {
+            log::error!("Failed to load OpenID Connect providers: {e}");
+            html! {}
+        }
+    };
+
+    Ok(result_html)
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct OidcProviderButtonProps {
+    provider: OidcProvider,
+}
+
+#[function_component(OidcProviderButton)]
+fn oidc_provider_button(props: &OidcProviderButtonProps) -> Html {
+    let error = use_state(|| None::<String>);
+
+    let redirect = {
+        let provider = props.provider.clone();
+        let error = error.clone();
+        use_async(async move {
+            error.set(None);
+            if let Err(e) = start_login(&provider).await {
+                log::error!("Failed to start login with {}: {e}", provider.id);
+                error.set(Some(e));
+            }
+            Ok::<(), ()>(())
+        })
+    };
+
+    let is_redirecting = redirect.loading;
+    let onclick = Callback::from(move |event: MouseEvent| {
+        event.prevent_default();
+        redirect.run();
+    });
+
+    let maybe_error_alert = match &*error {
+        Some(e) => html! {
+            <Alert style={Color::Danger}>{format!("Could not continue with {}. ", props.provider.display_name)}{e}</Alert>
+        },
+        None => html! {},
+    };
+
+    html! {
+        <>
+            {maybe_error_alert}
+            <button type="button" class="w-100 mb-2 btn btn-outline-secondary rounded-3" disabled={is_redirecting} {onclick}>
+                <LoadingSpinner show={is_redirecting} size={Size::Small} />
+                {format!("Continue with {}", props.provider.display_name)}
+            </button>
+        </>
+    }
+}
@@ -0,0 +1,286 @@
+use std::{future::Future, pin::Pin};
+
+use api_types::v1::{PasskeyInfo, PasskeyRegistration, RenamePasskeyRequest};
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, suspense::use_future_with_deps};
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::use_async;
+
+use crate::{
+    api::*,
+    components::{AsyncButton, Button, LoadingSpinner, Size},
+    utils::webauthn::{are_passkeys_supported, create_passkey},
+};
+
+/// Runs the whole passkey registration ceremony:
+/// fetch a challenge, let the browser create a passkey for it, and send the attestation back.
+async fn register_passkey(name: String) -> Result<(), String> {
+    let challenge = match auth_passkey_register_start().await {
+        Ok(ResponseType_auth_passkey_register_start::Status200(challenge)) => challenge,
+        Err(e) => return Err(format!("Could not reach the server: {e}")),
+    };
+    let credential = create_passkey(challenge.options).await?;
+    match auth_passkey_register_finish(challenge.id, PasskeyRegistration { name, credential })
+        .await
+    {
+        Ok(ResponseType_auth_passkey_register_finish::Status201(info)) => {
+            log::info!("Registered passkey: {}", info.id);
+            Ok(())
+        }
+        Ok(_) => Err("The server did not accept this passkey. Please try again.".to_string()),
+        Err(e) => Err(format!("Could not reach the server: {e}")),
+    }
+}
+
+#[function_component(PasskeyList)]
+pub fn passkey_list() -> Html {
+    let fallback = html! {
+        <>
+            <PasskeyListFallback />
+            <PasskeyListFallback />
+        </>
+    };
+
+    html! {
+        <>
+        <h2>{ "Passkeys" }</h2>
+        <p>
+            { "Passkeys let you sign in with your device's screen lock or a security key instead of a password." }
+        </p>
+        <Suspense {fallback}>
+            <PasskeyListInner />
+        </Suspense>
+        </>
+    }
+}
+
+#[function_component(PasskeyListInner)]
+fn passkey_list_inner() -> HtmlResult {
+    let refresh_pulse = use_state(|| false);
+    let refresh_pulse_out = refresh_pulse.clone();
+
+    let passkeys = use_future_with_deps(
+        |_refresh_pulse_state| async { auth_get_passkeys().await },
+        refresh_pulse_out,
+    );
+    let passkeys = passkeys?;
+
+    let new_name = use_state(String::new);
+    let register_error = use_state(|| None::<String>);
+
+    let register = {
+        let new_name = new_name.clone();
+        let register_error = register_error.clone();
+        let refresh_pulse = refresh_pulse.clone();
+        use_async(async move {
+            register_error.set(None);
+            match register_passkey((*new_name).clone()).await {
+                Ok(()) => {
+                    new_name.set(String::new());
+                    refresh_pulse.set(!*refresh_pulse);
+                }
+                Err(e) => {
+                    log::error!("Failed to register passkey: {e}");
+                    register_error.set(Some(e));
+                }
+            }
+            Ok::<(), ()>(())
+        })
+    };
+
+    let oninput_name = {
+        let new_name = new_name.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            new_name.set(input.value());
+        })
+    };
+
+    let on_change = {
+        let refresh_pulse = refresh_pulse.clone();
+        Callback::from(move |_| refresh_pulse.set(!*refresh_pulse))
+    };
+
+    let register_html = if are_passkeys_supported() {
+        let maybe_error_alert = match &*register_error {
+            Some(e) => html! {
+                <Alert style={Color::Danger}>{"Could not register a passkey. "}{e}</Alert>
+            },
+            None => html! {},
+        };
+        let is_registering = register.loading;
+        let onclick = Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            register.run();
+        });
+        html! {
+            <>
+                {maybe_error_alert}
+                <form class="input-group mb-3">
+                    <input type="text" class="form-control" placeholder="Passkey name, e.g. \"Work laptop\""
+                        value={(*new_name).clone()} oninput={oninput_name} />
+                    <button type="submit" class="btn btn-primary" disabled={is_registering || new_name.is_empty()} {onclick}>
+                        <LoadingSpinner show={is_registering} size={Size::Small} />
+                        { "Register a passkey" }
+                    </button>
+                </form>
+            </>
+        }
+    } else {
+        html! {
+            <Alert style={Color::Secondary}>{"This browser does not support passkeys."}</Alert>
+        }
+    };
+
+    let result_html = match &*passkeys {
+        Ok(ResponseType_auth_get_passkeys::Status200(passkeys)) => {
+            let passkey_rows = if passkeys.is_empty() {
+                html! { <p>{"You have not registered any passkeys yet."}</p> }
+            } else {
+                passkeys
+                    .iter()
+                    .map(|passkey| {
+                        html! {
+                            <PasskeyListRow key={passkey.id.to_string()} passkey={passkey.clone()} on_change={on_change.clone()} />
+                        }
+                    })
+                    .collect::<Html>()
+            };
+
+            html! {
+                <>
+                    {register_html}
+                    {passkey_rows}
+                </>
+            }
+        }
+        Err(_) => {
+            html! {
+                <Alert style={Color::Danger}>{"Failed to load your passkeys. Try reloading the page."}</Alert>
+            }
+        }
+    };
+
+    Ok(result_html)
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct PasskeyListRowProps {
+    passkey: PasskeyInfo,
+    on_change: Callback<()>,
+}
+
+#[function_component(PasskeyListRow)]
+fn passkey_list_row(props: &PasskeyListRowProps) -> Html {
+    let is_renaming = use_state(|| false);
+    let name = use_state(|| props.passkey.name.clone());
+    let passkey_id = props.passkey.id;
+
+    let rename_fn = {
+        let name = name.clone();
+        let is_renaming = is_renaming.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let name = name.clone();
+            let is_renaming = is_renaming.clone();
+            let on_change = on_change.clone();
+            Box::pin(async move {
+                let request = RenamePasskeyRequest {
+                    name: (*name).clone(),
+                };
+                match auth_rename_passkey(passkey_id, request).await {
+                    Ok(ResponseType_auth_rename_passkey::Status204(_)) => {
+                        is_renaming.set(false);
+                        on_change.emit(());
+                    }
+                    _ => log::error!("Failed to rename passkey: {passkey_id}"),
+                }
+            })
+        })
+    };
+
+    let delete_fn = {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let on_change = on_change.clone();
+            Box::pin(async move {
+                match auth_delete_passkey(passkey_id).await {
+                    // If it is already missing, it's deleted
+                    Ok(_) => on_change.emit(()),
+                    Err(_) => log::error!("Failed to delete passkey: {passkey_id}"),
+                }
+            })
+        })
+    };
+
+    let title_html = if *is_renaming {
+        let oninput = {
+            let name = name.clone();
+            Callback::from(move |event: InputEvent| {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                name.set(input.value());
+            })
+        };
+        let cancel = {
+            let name = name.clone();
+            let is_renaming = is_renaming.clone();
+            let original_name = props.passkey.name.clone();
+            Callback::from(move |_| {
+                name.set(original_name.clone());
+                is_renaming.set(false);
+            })
+        };
+        html! {
+            <div class="input-group mb-2">
+                <input type="text" class="form-control" value={(*name).clone()} {oninput} />
+                <AsyncButton class="" text="Save" color={crate::components::Color::Primary} onclick_fn={rename_fn} enabled={!name.is_empty()} />
+                <Button class="" text="Cancel" color={crate::components::Color::Secondary} onclick={cancel} />
+            </div>
+        }
+    } else {
+        html! { <h5 class="card-title">{&props.passkey.name}</h5> }
+    };
+
+    let last_used = match props.passkey.last_used {
+        Some(last_used) => format!("Last used at: {last_used}"),
+        None => "Never used".to_string(),
+    };
+
+    let start_renaming = {
+        let is_renaming = is_renaming.clone();
+        Callback::from(move |_| is_renaming.set(true))
+    };
+
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                {title_html}
+                <p class="card-text">
+                    {format!("Registered at: {}", props.passkey.created)}<br />
+                    {last_used}
+                </p>
+                <div class="btn-group" role="group">
+                    <Button class="" text="Rename" color={crate::components::Color::Secondary} onclick={start_renaming} enabled={!*is_renaming} />
+                    <AsyncButton class="" text="Delete" color={crate::components::Color::Danger} onclick_fn={delete_fn} />
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[function_component(PasskeyListFallback)]
+fn passkey_list_fallback() -> Html {
+    html! {
+            <div class="card mb-3">
+                <div class="card-body">
+                    <h5 class="card-title" aria-hidden="true">
+                        <span class="placeholder col-3"></span>
+                        <LoadingSpinner show={true} size={Size::Small} />
+                    </h5>
+                    <p class="card-text">
+                        <span class="placeholder col-6"></span>
+                    </p>
+                </div>
+            </div>
+    }
+}
@@ -0,0 +1,46 @@
+use api_types::v1::PasswordPolicy;
+use yew::prelude::*;
+
+use crate::utils::password_strength::{estimate_entropy_bits, unmet_requirements, PasswordStrength};
+
+#[derive(Properties, PartialEq)]
+pub struct PasswordStrengthMeterProps {
+    pub password: AttrValue,
+    pub policy: PasswordPolicy,
+}
+
+/// Bar showing how strong a password is, with a list of what it still needs to satisfy the policy.
+/// Meant to go right under the password [`FormTextBox`](crate::components::FormTextBox).
+#[function_component(PasswordStrengthMeter)]
+pub fn password_strength_meter(props: &PasswordStrengthMeterProps) -> Html {
+    if props.password.is_empty() {
+        return html! {};
+    }
+
+    let bits = estimate_entropy_bits(&props.password);
+    let strength = PasswordStrength::from_bits(bits);
+    let (width, color) = match strength {
+        PasswordStrength::VeryWeak => (10, "bg-danger"),
+        PasswordStrength::Weak => (30, "bg-danger"),
+        PasswordStrength::Fair => (55, "bg-warning"),
+        PasswordStrength::Strong => (80, "bg-success"),
+        PasswordStrength::VeryStrong => (100, "bg-success"),
+    };
+
+    let missing = unmet_requirements(&props.password, &props.policy)
+        .into_iter()
+        .map(|requirement| html! { <li>{requirement}</li> })
+        .collect::<Html>();
+
+    html! {
+        <div class="mb-3">
+            <div class="progress" role="progressbar" aria-label="Password strength" aria-valuenow={width.to_string()} aria-valuemin="0" aria-valuemax="100" style="height: 0.5rem;">
+                <div class={classes!("progress-bar", color)} style={format!("width: {width}%;")}></div>
+            </div>
+            <small class="text-body-secondary">{format!("Password strength: {}", strength.name())}</small>
+            <ul class="small text-danger mb-0">
+                {missing}
+            </ul>
+        </div>
+    }
+}
@@ -0,0 +1,156 @@
+use std::{future::Future, pin::Pin};
+
+use api_types::v1::RecoveryCodes;
+use yew::{prelude::*, suspense::use_future};
+use yew_bootstrap::{component::Alert, util::Color};
+
+use crate::{
+    api::*,
+    components::{AsyncButton, ConfirmButton},
+    utils::encode_uri_component,
+};
+
+/// Section of the profile page that lets the user generate one-time recovery codes
+/// for their two-factor authentication.
+#[function_component(RecoveryCodeManager)]
+pub fn recovery_code_manager() -> Html {
+    let fallback = html! {
+        <p class="placeholder-glow">
+            <span class="placeholder col-6"></span>
+        </p>
+    };
+
+    html! {
+        <>
+        <h2>{ "Recovery codes" }</h2>
+        <p>
+            { "If you lose access to your two-factor device, you can sign in with one of these one-time codes instead." }
+        </p>
+        <Suspense {fallback}>
+            <RecoveryCodeManagerInner />
+        </Suspense>
+        </>
+    }
+}
+
+#[function_component(RecoveryCodeManagerInner)]
+fn recovery_code_manager_inner() -> HtmlResult {
+    let status = use_future(|| async { auth_get_recovery_codes_status().await })?;
+
+    // The API only returns the codes once, right after generating them,
+    // so they are kept here until the user navigates away.
+    let fresh_codes = use_state(|| None::<RecoveryCodes>);
+    let is_generation_error = use_state(|| false);
+
+    let generate_fn = {
+        let fresh_codes = fresh_codes.clone();
+        let is_generation_error = is_generation_error.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let fresh_codes = fresh_codes.clone();
+            let is_generation_error = is_generation_error.clone();
+            Box::pin(async move {
+                match auth_generate_recovery_codes().await {
+                    Ok(ResponseType_auth_generate_recovery_codes::Status200(codes)) => {
+                        fresh_codes.set(Some(codes));
+                        is_generation_error.set(false);
+                    }
+                    _ => {
+                        log::error!("Failed to generate recovery codes");
+                        is_generation_error.set(true);
+                    }
+                }
+            })
+        })
+    };
+
+    let maybe_error_alert = if *is_generation_error {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to generate new recovery codes. Your existing codes are still valid."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    let result_html = match &*status {
+        Ok(ResponseType_auth_get_recovery_codes_status::Status404(_)) => html! {
+            <Alert style={Color::Secondary}>
+                {"Recovery codes become available once two-factor authentication is enabled on your account."}
+            </Alert>
+        },
+        Ok(ResponseType_auth_get_recovery_codes_status::Status200(status)) => {
+            let codes_html = match &*fresh_codes {
+                Some(codes) => html! { <RecoveryCodeDisplay codes={codes.codes.clone()} /> },
+                None if status.remaining == 0 => html! {
+                    <p>{"You have no unused recovery codes."}</p>
+                },
+                None => html! {
+                    <p>{format!("You have {} unused recovery codes.", status.remaining)}</p>
+                },
+            };
+
+            let has_codes = fresh_codes.is_some() || status.remaining > 0;
+            let actions_html = if !has_codes {
+                html! {
+                    <AsyncButton class="mb-3" text="Generate recovery codes" color={crate::components::Color::Primary} onclick_fn={generate_fn} />
+                }
+            } else {
+                html! {
+                    <ConfirmButton id="confirm-regenerate-recovery-codes" class="mb-3" text="Regenerate recovery codes"
+                        color={crate::components::Color::Secondary} onclick_fn={generate_fn}
+                        title="Replace your recovery codes?" confirm_text="Replace my codes">
+                        <p>{"Generating new codes will invalidate all of your existing recovery codes."}</p>
+                    </ConfirmButton>
+                }
+            };
+
+            html! {
+                <>
+                    {maybe_error_alert}
+                    {codes_html}
+                    {actions_html}
+                </>
+            }
+        }
+        Err(_) => html! {
+            <Alert style={Color::Danger}>{"Failed to load your recovery codes. Try reloading the page."}</Alert>
+        },
+    };
+
+    Ok(result_html)
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct RecoveryCodeDisplayProps {
+    codes: Vec<String>,
+}
+
+/// Shows freshly generated recovery codes, with a link to save them as a text file.
+#[function_component(RecoveryCodeDisplay)]
+fn recovery_code_display(props: &RecoveryCodeDisplayProps) -> Html {
+    let file_contents = format!(
+        "Oyasumi recovery codes\nEach code can only be used once.\n\n{}\n",
+        props.codes.join("\n")
+    );
+    #[allow(unused_unsafe)]
+    let href = format!("data:text/plain;charset=utf-8,{}", unsafe {
+        encode_uri_component(&file_contents)
+    });
+
+    let code_items = props
+        .codes
+        .iter()
+        .map(|code| html! { <li class="list-group-item font-monospace">{code}</li> })
+        .collect::<Html>();
+
+    html! {
+        <>
+            <Alert style={Color::Warning}>
+                {"Save these codes somewhere safe. Each of them works only once, and they will not be shown again."}
+            </Alert>
+            <ul class="list-group mb-3">
+                {code_items}
+            </ul>
+            <a class="btn btn-primary mb-3 me-2" {href} download="oyasumi-recovery-codes.txt">{"Download as text file"}</a>
+        </>
+    }
+}
@@ -0,0 +1,132 @@
+use api_types::{
+    v1::{SecondFactorCode, SecondFactorRequest},
+    Snowflake,
+};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::use_async;
+
+use crate::{
+    api::*,
+    components::{FormSubmitBtn, FormTextBox, LoadingSpinner, Size},
+};
+
+#[derive(Properties, PartialEq)]
+pub struct SecondFactorFormProps {
+    /// The challenge the server answered the password login with.
+    pub challenge_id: Snowflake,
+    /// Go back to the password form, e.g. because the challenge has expired.
+    pub on_cancel: Callback<()>,
+    /// Called once the code has been accepted and the session cookie is set.
+    pub on_success: Callback<()>,
+}
+
+/// Second login step for accounts with two-factor authentication.
+/// Takes a code from the authenticator app, or one of the recovery codes if the user has lost access to it.
+#[function_component(SecondFactorForm)]
+pub fn second_factor_form(props: &SecondFactorFormProps) -> Html {
+    let code = use_state(String::new);
+    let is_recovery_code = use_state(|| false);
+    let error = use_state(|| None::<&'static str>);
+
+    let submit_code = {
+        let code = code.clone();
+        let is_recovery_code = is_recovery_code.clone();
+        let error = error.clone();
+        let challenge_id = props.challenge_id;
+        let on_success = props.on_success.clone();
+        use_async(async move {
+            error.set(None);
+            let entered = code.trim().to_string();
+            let request = SecondFactorRequest {
+                code: match *is_recovery_code {
+                    true => SecondFactorCode::RecoveryCode(entered),
+                    false => SecondFactorCode::Totp(entered),
+                },
+            };
+            match auth_login_second_factor(challenge_id, request).await {
+                Ok(ResponseType_auth_login_second_factor::Status200(_)) => on_success.emit(()),
+                Ok(ResponseType_auth_login_second_factor::Status401(_)) => {
+                    error.set(Some(match *is_recovery_code {
+                        true => "This recovery code is not valid, or has already been used.",
+                        false => "This code is not valid. Codes change every 30 seconds, so try the current one.",
+                    }));
+                }
+                Ok(ResponseType_auth_login_second_factor::Status404(_)) => {
+                    error.set(Some(
+                        "This login attempt has expired. Go back and enter your password again.",
+                    ));
+                }
+                Err(e) => {
+                    log::error!("Failed to send the second factor: {e}");
+                    error.set(Some("Could not reach the server. Try again."));
+                }
+            }
+            Ok::<(), ()>(())
+        })
+    };
+
+    let oninput_code = {
+        let code = code.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            code.set(input.value());
+        })
+    };
+
+    let is_submitting = submit_code.loading;
+    let onsubmit = Callback::from(move |event: MouseEvent| {
+        event.prevent_default();
+        submit_code.run();
+    });
+
+    let ontoggle = {
+        let is_recovery_code = is_recovery_code.clone();
+        let code = code.clone();
+        let error = error.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            code.set(String::new());
+            error.set(None);
+            is_recovery_code.set(!*is_recovery_code);
+        })
+    };
+
+    let onback = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            on_cancel.emit(());
+        })
+    };
+
+    let maybe_error_alert = match *error {
+        Some(message) => html! {
+            <Alert style={Color::Danger}>{message}</Alert>
+        },
+        None => html! {},
+    };
+
+    let (label, toggle_text) = match *is_recovery_code {
+        true => ("Recovery code", "Use your authenticator app instead"),
+        false => (
+            "Code from your authenticator app",
+            "Use a recovery code instead",
+        ),
+    };
+
+    html! {
+        <form>
+            <p>{"Your account is protected with two-factor authentication."}</p>
+            {maybe_error_alert}
+            <FormTextBox id="second_factor_code" input_type="text" label={label} value={(*code).clone()} oninput={oninput_code} />
+            <FormSubmitBtn onclick={onsubmit} enabled={!is_submitting && !code.trim().is_empty()}>
+                <LoadingSpinner show={is_submitting} size={Size::Small} />
+                { "Verify" }
+            </FormSubmitBtn>
+            <button type="button" class="w-100 mb-2 btn btn-link" onclick={ontoggle}>{toggle_text}</button>
+            <button type="button" class="w-100 mb-2 btn btn-link" onclick={onback}>{"Back"}</button>
+        </form>
+    }
+}
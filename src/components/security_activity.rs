@@ -0,0 +1,140 @@
+use api_types::{
+    v1::{SecurityEvent, SecurityEventKind},
+    Snowflake,
+};
+use yew::prelude::*;
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::use_async;
+
+use crate::{
+    api::*,
+    components::{LoadingSpinner, Size},
+    utils::user_agent::{describe_device, describe_location},
+};
+
+fn describe_event_kind(kind: &SecurityEventKind) -> &'static str {
+    match kind {
+        SecurityEventKind::LoginSucceeded => "Signed in",
+        SecurityEventKind::LoginFailed => "Failed sign-in attempt",
+        SecurityEventKind::Logout => "Signed out",
+        SecurityEventKind::SessionRevoked => "Session revoked",
+        SecurityEventKind::PasswordChanged => "Password changed",
+        SecurityEventKind::RegistrationConfirmed => "Account created",
+    }
+}
+
+/// Timeline of security-relevant events on the account, newest first.
+///
+/// Events are loaded a page at a time; events the API flags as suspicious are highlighted.
+#[function_component(SecurityActivity)]
+pub fn security_activity() -> Html {
+    let events = use_state(Vec::<SecurityEvent>::new);
+    let next_cursor = use_state(|| None::<Snowflake>);
+    let is_finished = use_state(|| false);
+
+    let load_page = {
+        let events = events.clone();
+        let next_cursor = next_cursor.clone();
+        let is_finished = is_finished.clone();
+        use_async(async move {
+            let query = SecurityEventsQuery {
+                before: *next_cursor,
+            };
+            match auth_get_security_events(query).await {
+                Ok(ResponseType_auth_get_security_events::Status200(page)) => {
+                    let mut all_events = (*events).clone();
+                    all_events.extend(page.events);
+                    events.set(all_events);
+                    is_finished.set(page.next_cursor.is_none());
+                    next_cursor.set(page.next_cursor);
+                    Ok(())
+                }
+                Err(e) => {
+                    log::error!("Failed to load security events: {e}");
+                    Err(())
+                }
+            }
+        })
+    };
+
+    {
+        let load_page = load_page.clone();
+        use_effect_with_deps(move |_| load_page.run(), ());
+    }
+
+    let event_rows = events
+        .iter()
+        .map(|event| {
+            let device = describe_device(event.user_agent.as_deref());
+            let location =
+                describe_location(event.location.as_deref(), event.ip_address.as_deref());
+            let item_class = event.suspicious.then_some("list-group-item-danger");
+            html! {
+                <li key={event.id.to_string()} class={classes!("list-group-item", item_class)}>
+                    <div class="d-flex justify-content-between">
+                        <strong>
+                            {describe_event_kind(&event.kind)}
+                            if event.suspicious {
+                                <span class="badge text-bg-danger ms-2">{"Suspicious"}</span>
+                            }
+                        </strong>
+                        <small>{event.time.to_string()}</small>
+                    </div>
+                    <small class="text-body-secondary">{format!("{device}, {location}")}</small>
+                </li>
+            }
+        })
+        .collect::<Html>();
+
+    let is_first_page_error = load_page.error.is_some() && events.is_empty();
+    let maybe_error_alert = if is_first_page_error {
+        let onretry = {
+            let load_page = load_page.clone();
+            Callback::from(move |_| load_page.run())
+        };
+        html! {
+            <Alert style={Color::Danger}>
+                {"Failed to load your security activity. "}
+                <button type="button" class="btn btn-sm btn-danger" onclick={onretry}>{"Retry"}</button>
+            </Alert>
+        }
+    } else if load_page.error.is_some() {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to load older activity. Try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    let footer = if load_page.loading {
+        html! {
+            <p><LoadingSpinner show={true} size={Size::Small} />{"Loading activity..."}</p>
+        }
+    } else if events.is_empty() && *is_finished {
+        html! { <p>{"There is no activity on your account yet."}</p> }
+    } else if !*is_finished && !is_first_page_error {
+        let onclick = {
+            let load_page = load_page.clone();
+            Callback::from(move |_| load_page.run())
+        };
+        html! {
+            <button class="btn btn-secondary mb-3" {onclick}>{"Load older activity"}</button>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <>
+        <h2>{ "Security activity" }</h2>
+        <p>
+            { "Sign-ins and other changes to your account's security. If something here wasn't you, change your password and revoke your sessions." }
+        </p>
+        <ul class="list-group mb-3">
+            {event_rows}
+        </ul>
+        {maybe_error_alert}
+        {footer}
+        </>
+    }
+}
@@ -0,0 +1,358 @@
+use std::{future::Future, pin::Pin, rc::Rc};
+
+use api_types::{v1::LoginRequest, Snowflake};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_bootstrap::{component::Alert, util::Color};
+use yew_hooks::{use_async, use_interval, use_update};
+use yew_router::prelude::use_navigator;
+
+use crate::{
+    api::*,
+    components::{AsyncButton, FormSubmitBtn, FormTextBox, LoadingSpinner, SecondFactorForm, Size},
+    context::{UserContext, UserContextSetter, UserSettingsContext},
+    utils::auth_events::{broadcast_auth_event, AuthEvent},
+    utils::get_current_time,
+    Route,
+};
+
+/// The warning times offered in the profile settings, in minutes.
+const WARNING_TIME_CHOICES: [u32; 4] = [5, 10, 30, 60];
+
+#[derive(Properties, PartialEq)]
+pub struct SessionExpiryWarningProps {
+    /// How many minutes before the session expires the banner starts showing,
+    /// unless the user has chosen a different time in their settings.
+    #[prop_or(10)]
+    pub warn_before_minutes: i64,
+}
+
+/// Banner that counts down to the expiry of the current session,
+/// and lets the user sign in again without leaving the page.
+///
+/// It's expected that this is placed inside the dashboard, below the [`UserContext`], [`UserContextSetter`]
+/// and [`UserSettingsContext`] providers.
+#[function_component(SessionExpiryWarning)]
+pub fn session_expiry_warning(props: &SessionExpiryWarningProps) -> Html {
+    let user_ctx = use_context::<Rc<UserContext>>()
+        .expect("UserContext not found while rendering SessionExpiryWarning");
+    let settings_ctx = use_context::<UserSettingsContext>()
+        .expect("UserSettingsContext not found while rendering SessionExpiryWarning");
+    let is_reauthenticating = use_state(|| false);
+
+    let update = use_update();
+    use_interval(move || update(), 1000);
+
+    let expires = match user_ctx.as_ref() {
+        UserContext::LoggedIn(token_data) => token_data.token.expires,
+        UserContext::LoggedOut => return html! {},
+    };
+    let warn_before_minutes = settings_ctx
+        .settings
+        .as_ref()
+        .and_then(|settings| settings.session_warning_minutes)
+        .map(i64::from)
+        .unwrap_or(props.warn_before_minutes);
+    let remaining = expires - get_current_time();
+    if remaining.num_minutes() >= warn_before_minutes {
+        return html! {};
+    }
+
+    let open_modal = {
+        let is_reauthenticating = is_reauthenticating.clone();
+        Callback::from(move |_| is_reauthenticating.set(true))
+    };
+    let close_modal = {
+        let is_reauthenticating = is_reauthenticating.clone();
+        Callback::from(move |_| is_reauthenticating.set(false))
+    };
+
+    let seconds = remaining.num_seconds();
+    let (alert_color, message, button_text) = if seconds > 0 {
+        (
+            Color::Warning,
+            format!(
+                "Your session expires in {}:{:02}.",
+                seconds / 60,
+                seconds % 60
+            ),
+            "Stay signed in",
+        )
+    } else {
+        (
+            Color::Danger,
+            "Your session has expired.".to_string(),
+            "Sign in again",
+        )
+    };
+
+    let modal = if *is_reauthenticating {
+        html! { <ReauthenticateModal on_close={close_modal} /> }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <>
+            <Alert style={alert_color}>
+                <div class="d-flex align-items-center">
+                    <span class="me-auto">{message}</span>
+                    <button type="button" class="btn btn-sm btn-primary" onclick={open_modal}>{button_text}</button>
+                </div>
+            </Alert>
+            {modal}
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ReauthenticateModalProps {
+    on_close: Callback<()>,
+}
+
+/// Overlay with a login form that refreshes the [`UserContext`] in place,
+/// so that the page underneath (and anything typed into it) is kept.
+///
+/// That is only done for the same account. Signing in as someone else goes through a full logout,
+/// so the previous user's page is not left on screen.
+#[function_component(ReauthenticateModal)]
+fn reauthenticate_modal(props: &ReauthenticateModalProps) -> Html {
+    let context_setter = use_context::<UserContextSetter>()
+        .expect("UserContextSetter not found while rendering ReauthenticateModal");
+    let user_ctx = use_context::<Rc<UserContext>>()
+        .expect("UserContext not found while rendering ReauthenticateModal");
+    let navigator = use_navigator().unwrap();
+    let login = use_state(String::new);
+    let password = use_state(String::new);
+    let is_error = use_state(|| false);
+    // Set once the password was accepted, if the account also needs a second factor.
+    let second_factor_challenge = use_state(|| None::<Snowflake>);
+
+    // Runs once the new session cookie is set, after the password and possibly a second factor.
+    let finish_relogin = {
+        let is_error = is_error.clone();
+        let on_close = props.on_close.clone();
+        let expiring_user_id = match user_ctx.as_ref() {
+            UserContext::LoggedIn(token_data) => Some(token_data.token.user_id),
+            UserContext::LoggedOut => None,
+        };
+        use_async(async move {
+            // The new token only becomes visible to us through the API.
+            if let Ok(ResponseType_auth_get_current_token::Status200(token_data)) =
+                auth_get_current_token().await
+            {
+                if Some(token_data.token.user_id) != expiring_user_id {
+                    log::info!("Signed in as a different user, logging out of the current page");
+                    if auth_logout().await.is_err() {
+                        log::error!("Failed to log out!");
+                    }
+                    broadcast_auth_event(AuthEvent::LoggedOut);
+                    navigator.push(&Route::Login);
+                    return Ok(());
+                }
+                log::info!(
+                    "Re-authenticated, session now expires at {}",
+                    token_data.token.expires
+                );
+                context_setter.0.emit(UserContext::LoggedIn(token_data));
+                broadcast_auth_event(AuthEvent::LoggedIn);
+                on_close.emit(());
+                return Ok(());
+            }
+            log::info!("Failed to re-authenticate!");
+            is_error.set(true);
+            Ok::<(), ()>(())
+        })
+    };
+
+    let relogin = {
+        let login = login.clone();
+        let password = password.clone();
+        let is_error = is_error.clone();
+        let second_factor_challenge = second_factor_challenge.clone();
+        let finish_relogin = finish_relogin.clone();
+        use_async(async move {
+            is_error.set(false);
+            let request = LoginRequest::EmailPassword {
+                email: (*login).clone(),
+                password: (*password).clone().into(),
+            };
+            match auth_login(request).await {
+                Ok(ResponseType_auth_login::Status200(_)) => finish_relogin.run(),
+                Ok(ResponseType_auth_login::Status202(challenge)) => {
+                    log::info!("Password accepted, asking for the second factor");
+                    second_factor_challenge.set(Some(challenge.id));
+                }
+                _ => {
+                    log::info!("Failed to re-authenticate!");
+                    is_error.set(true);
+                }
+            }
+            Ok::<(), ()>(())
+        })
+    };
+
+    let oninput_login = {
+        let login = login.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            login.set(input.value());
+        })
+    };
+
+    let oninput_pw = {
+        let password = password.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            password.set(input.value());
+        })
+    };
+
+    let is_logging_in = relogin.loading || finish_relogin.loading;
+    let onsubmit = Callback::from(move |event: MouseEvent| {
+        event.prevent_default();
+        relogin.run();
+    });
+
+    let onclose = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    let on_second_factor_cancel = {
+        let second_factor_challenge = second_factor_challenge.clone();
+        Callback::from(move |_| second_factor_challenge.set(None))
+    };
+    let on_second_factor_success = Callback::from(move |_| {
+        second_factor_challenge.set(None);
+        finish_relogin.run();
+    });
+
+    let maybe_error_alert = if *is_error {
+        html! {
+            <Alert style={Color::Danger}>{"Could not sign you in. Check your details and try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class="modal d-block" tabindex="-1" role="dialog" style="background-color: rgba(0, 0, 0, 0.5);">
+            <div class="modal-dialog modal-dialog-centered" role="document">
+                <div class="modal-content">
+                    <div class="modal-header">
+                        <h5 class="modal-title">{"Sign in again"}</h5>
+                        <button type="button" class="btn-close" aria-label="Close" onclick={onclose}></button>
+                    </div>
+                    <div class="modal-body">
+                        {maybe_error_alert}
+                        if let Some(challenge_id) = *second_factor_challenge {
+                            <SecondFactorForm {challenge_id} on_cancel={on_second_factor_cancel} on_success={on_second_factor_success} />
+                        } else {
+                            <form>
+                                <FormTextBox id="reauth_login" input_type="text" label="Username or Email" value={(*login).clone()} oninput={oninput_login} />
+                                <FormTextBox id="reauth_password" input_type="password" label="Password" value={(*password).clone()} oninput={oninput_pw} />
+                                <FormSubmitBtn onclick={onsubmit} enabled={!is_logging_in}>
+                                    <LoadingSpinner show={is_logging_in} size={Size::Small} />
+                                    { "Sign in" }
+                                </FormSubmitBtn>
+                            </form>
+                        }
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Profile setting for how early [`SessionExpiryWarning`] warns about the session expiring.
+#[function_component(SessionExpiryWarningSettings)]
+pub fn session_expiry_warning_settings() -> Html {
+    let settings_ctx = use_context::<UserSettingsContext>()
+        .expect("UserSettingsContext not found while rendering SessionExpiryWarningSettings");
+    let selected = use_state(|| None::<Option<u32>>);
+    let is_save_error = use_state(|| false);
+
+    let settings = match &settings_ctx.settings {
+        Some(settings) => settings.clone(),
+        None => {
+            return html! {
+                <>
+                <h2>{ "Session expiry warning" }</h2>
+                <p class="placeholder-glow"><span class="placeholder col-6"></span></p>
+                </>
+            }
+        }
+    };
+    let current = (*selected).unwrap_or(settings.session_warning_minutes);
+
+    let onchange = {
+        let selected = selected.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            selected.set(Some(select.value().parse().ok()));
+        })
+    };
+
+    let save_fn = {
+        let settings_ctx = settings_ctx.clone();
+        let is_save_error = is_save_error.clone();
+        let selected = selected.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let settings_ctx = settings_ctx.clone();
+            let is_save_error = is_save_error.clone();
+            let selected = selected.clone();
+            let mut settings = settings.clone();
+            settings.session_warning_minutes = current;
+            Box::pin(async move {
+                match user_put_settings(settings.clone()).await {
+                    Ok(ResponseType_user_put_settings::Status204(_)) => {
+                        is_save_error.set(false);
+                        selected.set(None);
+                        settings_ctx.set.emit(settings);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to save settings: {e}");
+                        is_save_error.set(true);
+                    }
+                }
+            })
+        })
+    };
+
+    let options = WARNING_TIME_CHOICES
+        .iter()
+        .map(|minutes| {
+            html! {
+                <option value={minutes.to_string()} selected={current == Some(*minutes)}>
+                    {format!("{minutes} minutes before it expires")}
+                </option>
+            }
+        })
+        .collect::<Html>();
+
+    let maybe_error_alert = if *is_save_error {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to save your setting. Try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <>
+        <h2>{ "Session expiry warning" }</h2>
+        <p>
+            { "A banner lets you sign in again before your session expires, so you don't lose what you're working on." }
+        </p>
+        {maybe_error_alert}
+        <div class="input-group mb-3">
+            <select class="form-select" {onchange}>
+                <option value="" selected={current.is_none()}>{"Default (10 minutes before it expires)"}</option>
+                {options}
+            </select>
+            <AsyncButton class="" text="Save" color={crate::components::Color::Primary} onclick_fn={save_fn} enabled={selected.is_some()} />
+        </div>
+        </>
+    }
+}
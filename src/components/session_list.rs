@@ -1,15 +1,29 @@
-use std::rc::Rc;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+};
 
-use api_types::Snowflake;
-use yew::{prelude::*, suspense::use_future_with_deps};
+use api_types::{
+    v1::{RenameTokenRequest, TokenData},
+    Snowflake,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*, suspense::use_future_with_deps};
 use yew_bootstrap::{component::Alert, util::Color};
 use yew_hooks::use_async;
 use yew_router::prelude::use_navigator;
 
 use crate::{
     api::*,
-    components::{LoadingSpinner, Size},
-    context::UserContext,
+    components::{AsyncButton, Button, ConfirmButton, LoadingSpinner, Size},
+    context::{UserContext, UserContextSetter},
+    utils::auth_events::{broadcast_auth_event, AuthEvent},
+    utils::{
+        get_current_time,
+        user_agent::{describe_device, describe_location, describe_user_agent},
+    },
     Route,
 };
 
@@ -40,8 +54,102 @@ pub fn session_list() -> Html {
     }
 }
 
+/// Sessions this old or older are matched by [`SessionFilter::OlderThan30Days`].
+const OLD_SESSION_DAYS: i64 = 30;
+
+/// Which sessions to show, and to select with "Select all".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SessionFilter {
+    All,
+    OlderThan30Days,
+    /// Sessions on the same kind of device and browser as the current one.
+    SameDevice,
+}
+
+impl SessionFilter {
+    const ALL: [SessionFilter; 3] = [Self::All, Self::OlderThan30Days, Self::SameDevice];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::All => "All sessions",
+            Self::OlderThan30Days => "Older than 30 days",
+            Self::SameDevice => "Same device as this session",
+        }
+    }
+
+    fn matches(&self, session: &TokenData, current: &TokenData) -> bool {
+        match self {
+            Self::All => true,
+            Self::OlderThan30Days => {
+                let created = session.token.created.unwrap_or_else(|| session.token.id.timestamp());
+                (get_current_time() - created).num_days() >= OLD_SESSION_DAYS
+            }
+            Self::SameDevice => {
+                let device = |token_data: &TokenData| {
+                    token_data.token.user_agent.as_deref().map(describe_user_agent)
+                };
+                device(session).is_some() && device(session) == device(current)
+            }
+        }
+    }
+}
+
+/// How revoking a selected session went.
+#[derive(Clone, Debug, PartialEq)]
+enum RevocationStatus {
+    InProgress,
+    Revoked,
+    Failed(String),
+}
+
+/// The sessions that the rows have loaded, which of them are selected, and how revoking them went.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SessionSelection {
+    loaded: HashMap<Snowflake, TokenData>,
+    selected: HashSet<Snowflake>,
+    revocations: HashMap<Snowflake, RevocationStatus>,
+}
+
+enum SessionSelectionAction {
+    Loaded(TokenData),
+    Toggle(Snowflake),
+    SelectOnly(HashSet<Snowflake>),
+    SetRevocation(Snowflake, RevocationStatus),
+    /// Forget about finished revocations, e.g. after refreshing the list.
+    ClearRevocations,
+}
+
+impl Reducible for SessionSelection {
+    type Action = SessionSelectionAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut next = (*self).clone();
+        match action {
+            SessionSelectionAction::Loaded(token_data) => {
+                next.loaded.insert(token_data.token.id, token_data);
+            }
+            SessionSelectionAction::Toggle(id) => {
+                if !next.selected.remove(&id) {
+                    next.selected.insert(id);
+                }
+            }
+            SessionSelectionAction::SelectOnly(ids) => next.selected = ids,
+            SessionSelectionAction::SetRevocation(id, status) => {
+                if status == RevocationStatus::Revoked {
+                    next.selected.remove(&id);
+                }
+                next.revocations.insert(id, status);
+            }
+            SessionSelectionAction::ClearRevocations => next.revocations.clear(),
+        }
+        Rc::new(next)
+    }
+}
+
 #[function_component(SessionListInner)]
 fn session_list_inner() -> HtmlResult {
+    let current_session = use_context::<Rc<UserContext>>()
+        .expect("UserContext not found while rendering SessionListInner");
     let refresh_pulse = use_state(|| false);
     let refresh_pulse_out = refresh_pulse.clone();
 
@@ -54,6 +162,9 @@ fn session_list_inner() -> HtmlResult {
     );
     let sessions = sessions?;
 
+    let selection = use_reducer(SessionSelection::default);
+    let filter = use_state(|| SessionFilter::All);
+
     let revoke_many = {
         let refresh_pulse_out = refresh_pulse.clone();
         use_async(async move {
@@ -66,32 +177,193 @@ fn session_list_inner() -> HtmlResult {
         })
     };
 
+    let current_token = match &*current_session {
+        UserContext::LoggedOut => panic!("Logged out while rendering SessionListInner"),
+        UserContext::LoggedIn(token_data) => token_data.clone(),
+    };
+
     let result_html = match &*sessions {
         Ok(ResponseType_auth_get_tokens::Status200(token_snowflakes)) => {
+            // Sessions that are not loaded yet are shown, but can't be selected by the filter.
+            let is_visible = |id: &Snowflake| match selection.loaded.get(id) {
+                Some(token_data) => filter.matches(token_data, &current_token),
+                None => true,
+            };
+            // The current session is left out: revoking it has to go through its own button, which logs out.
+            let selectable = token_snowflakes
+                .iter()
+                .filter(|id| **id != current_token.token.id)
+                .filter(|id| selection.loaded.contains_key(id) && is_visible(id))
+                .filter(|id| selection.revocations.get(id) != Some(&RevocationStatus::Revoked))
+                .copied()
+                .collect::<HashSet<_>>();
+            let is_all_selected = !selectable.is_empty() && selectable.is_subset(&selection.selected);
+
+            let on_loaded = {
+                let dispatcher = selection.dispatcher();
+                Callback::from(move |token_data| dispatcher.dispatch(SessionSelectionAction::Loaded(token_data)))
+            };
+            let on_toggle = {
+                let dispatcher = selection.dispatcher();
+                Callback::from(move |id| dispatcher.dispatch(SessionSelectionAction::Toggle(id)))
+            };
+
             let session_list_rows = token_snowflakes
                 .iter()
                 .map(|token_snowflake| {
                     html! {
-                        <SessionListRow session_id={*token_snowflake} />
+                        <SessionListRow key={token_snowflake.to_string()} session_id={*token_snowflake}
+                            hidden={!is_visible(token_snowflake)}
+                            selected={selection.selected.contains(token_snowflake)}
+                            revocation={selection.revocations.get(token_snowflake).cloned()}
+                            on_loaded={on_loaded.clone()} on_toggle={on_toggle.clone()} />
                     }
                 })
                 .collect::<Html>();
 
+            let onchange_filter = {
+                let filter = filter.clone();
+                let dispatcher = selection.dispatcher();
+                Callback::from(move |event: Event| {
+                    let select: HtmlSelectElement = event.target_unchecked_into();
+                    let index: usize = select.value().parse().unwrap_or(0);
+                    filter.set(SessionFilter::ALL[index]);
+                    // Don't keep sessions selected that are no longer shown.
+                    dispatcher.dispatch(SessionSelectionAction::SelectOnly(HashSet::new()));
+                })
+            };
+            let filter_options = SessionFilter::ALL
+                .iter()
+                .enumerate()
+                .map(|(index, each_filter)| {
+                    html! {
+                        <option value={index.to_string()} selected={*each_filter == *filter}>{each_filter.name()}</option>
+                    }
+                })
+                .collect::<Html>();
+
+            let onchange_select_all = {
+                let dispatcher = selection.dispatcher();
+                let selectable = selectable.clone();
+                Callback::from(move |_: Event| {
+                    let ids = if is_all_selected {
+                        HashSet::new()
+                    } else {
+                        selectable.clone()
+                    };
+                    dispatcher.dispatch(SessionSelectionAction::SelectOnly(ids));
+                })
+            };
+
+            let revoke_selected = {
+                let dispatcher = selection.dispatcher();
+                let selected = selection.selected.clone();
+                Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+                    let dispatcher = dispatcher.clone();
+                    let selected = selected.clone();
+                    Box::pin(async move {
+                        for id in selected.iter().copied() {
+                            let dispatcher = dispatcher.clone();
+                            dispatcher.dispatch(SessionSelectionAction::SetRevocation(
+                                id,
+                                RevocationStatus::InProgress,
+                            ));
+                            spawn_local(async move {
+                                let status = match auth_delete_token(id).await {
+                                    // If it is already missing, it's revoked
+                                    Ok(_) => {
+                                        log::info!("Revoked token: {id}");
+                                        RevocationStatus::Revoked
+                                    }
+                                    Err(e) => {
+                                        log::error!("Failed to revoke token {id}: {e}");
+                                        RevocationStatus::Failed(e.to_string())
+                                    }
+                                };
+                                dispatcher
+                                    .dispatch(SessionSelectionAction::SetRevocation(id, status));
+                            });
+                        }
+                        // The progress is tracked per session, so there is nothing to wait for here.
+                    })
+                })
+            };
+
+            let in_progress = selection
+                .revocations
+                .values()
+                .filter(|status| **status == RevocationStatus::InProgress)
+                .count();
+            let revoked = selection
+                .revocations
+                .values()
+                .filter(|status| **status == RevocationStatus::Revoked)
+                .count();
+            let failed = selection.revocations.len() - in_progress - revoked;
+            let maybe_progress_alert = if selection.revocations.is_empty() {
+                html! {}
+            } else if in_progress > 0 {
+                html! {
+                    <Alert style={Color::Info}>
+                        <LoadingSpinner show={true} size={Size::Small} />
+                        {format!("Revoking {in_progress} sessions... ({revoked} done, {failed} failed)")}
+                    </Alert>
+                }
+            } else if failed > 0 {
+                html! {
+                    <Alert style={Color::Danger}>
+                        {format!("Revoked {revoked} sessions, but {failed} could not be revoked. They are still selected, so you can try again.")}
+                    </Alert>
+                }
+            } else {
+                html! {
+                    <Alert style={Color::Success}>{format!("Revoked {revoked} sessions.")}</Alert>
+                }
+            };
+
+            let selected_count = selection.selected.len();
+            let revoke_many_fn = Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+                let revoke_many = revoke_many.clone();
+                Box::pin(async move { revoke_many.run() })
+            });
+            let onclick_refresh = {
+                let dispatcher = selection.dispatcher();
+                Callback::from(move |_| {
+                    dispatcher.dispatch(SessionSelectionAction::ClearRevocations);
+                    refresh_pulse.set(!*refresh_pulse);
+                })
+            };
+
             html! {
                 <>
                     <div class="btn-group mb-3" role="group">
-                        <button class="btn btn-primary" onclick={Callback::from(move |_| {
-                            refresh_pulse.set(!*refresh_pulse);
-                        })}>
+                        <button class="btn btn-primary" onclick={onclick_refresh}>
                             { "Refresh" }
                         </button>
-                        <button class="btn btn-danger" onclick={Callback::from(move |_| {
-                            revoke_many.run();
-                        })}>
-                            { "Revoke all except current session" }
-                        </button>
+                        <ConfirmButton id="confirm-revoke-other-sessions" class="" text="Revoke all except current session"
+                            onclick_fn={revoke_many_fn} title="Revoke all other sessions?"
+                            confirm_text="Revoke all" type_to_confirm={AttrValue::from("revoke all")}>
+                            <p>{"Every device except this one will be logged out, including any you still use."}</p>
+                        </ConfirmButton>
                     </div>
 
+                    <div class="d-flex flex-wrap align-items-center gap-3 mb-3">
+                        <div class="form-check mb-0">
+                            <input class="form-check-input" type="checkbox" id="select_all_sessions"
+                                checked={is_all_selected} disabled={selectable.is_empty()} onchange={onchange_select_all} />
+                            <label class="form-check-label" for="select_all_sessions">{"Select all"}</label>
+                        </div>
+                        <select class="form-select w-auto" aria-label="Filter sessions" onchange={onchange_filter}>
+                            {filter_options}
+                        </select>
+                        <ConfirmButton id="confirm-revoke-selected-sessions" class=""
+                            text={format!("Revoke selected ({selected_count})")} enabled={selected_count > 0}
+                            spinning={in_progress > 0} onclick_fn={revoke_selected} title="Revoke the selected sessions?"
+                            confirm_text="Revoke">
+                            <p>{format!("{selected_count} devices will be logged out.")}</p>
+                        </ConfirmButton>
+                    </div>
+                    {maybe_progress_alert}
 
                     { session_list_rows }
                 </>
@@ -110,21 +382,56 @@ fn session_list_inner() -> HtmlResult {
 #[derive(Clone, Debug, PartialEq, Properties)]
 struct SessionListRowProps {
     session_id: Snowflake,
+    /// Whether the row is filtered out. It stays mounted so that it doesn't load again.
+    #[prop_or(false)]
+    hidden: bool,
+    #[prop_or(false)]
+    selected: bool,
+    /// How revoking this session as part of a selection went, if it was.
+    #[prop_or_default]
+    revocation: Option<RevocationStatus>,
+    /// Called with the session's details once they are loaded.
+    #[prop_or_default]
+    on_loaded: Callback<TokenData>,
+    #[prop_or_default]
+    on_toggle: Callback<Snowflake>,
 }
 
 #[function_component(SessionListRow)]
 fn session_list_row(props: &SessionListRowProps) -> HtmlResult {
     let current_session = use_context::<Rc<UserContext>>()
         .expect("UserContext not found while rendering SessionListRow");
+    let context_setter = use_context::<UserContextSetter>();
+    let refresh_pulse = use_state(|| false);
     let session = use_future_with_deps(
-        |session_id| async move {
-            let res = auth_get_token(*session_id).await;
+        |deps| async move {
+            let (session_id, _refresh_pulse) = *deps;
+            let res = auth_get_token(session_id).await;
             res
         },
-        props.session_id,
+        (props.session_id, *refresh_pulse),
     );
     let session = session?;
 
+    {
+        let loaded = match &*session {
+            Ok(ResponseType_auth_get_token::Status200(token_info)) => Some(token_info.clone()),
+            _ => None,
+        };
+        let on_loaded = props.on_loaded.clone();
+        use_effect_with_deps(
+            move |loaded| {
+                if let Some(token_info) = loaded {
+                    on_loaded.emit(token_info.clone());
+                }
+            },
+            loaded,
+        );
+    }
+
+    let is_renaming = use_state(|| false);
+    let new_name = use_state(String::new);
+
     let navigator = use_navigator().expect("Navigator not found while rendering SessionListRow");
 
     let token_id = use_state(|| props.session_id);
@@ -150,6 +457,7 @@ fn session_list_row(props: &SessionListRowProps) -> HtmlResult {
                 log::info!("Revoked token: {}", *token_id);
                 is_hidden.set(true);
                 if *is_current_session {
+                    broadcast_auth_event(AuthEvent::LoggedOut);
                     navigator.push(&Route::Login);
                 }
             } else {
@@ -160,33 +468,161 @@ fn session_list_row(props: &SessionListRowProps) -> HtmlResult {
         })
     };
 
-    if *is_hidden {
+    if *is_hidden || props.hidden || props.revocation == Some(RevocationStatus::Revoked) {
         return Ok(html! {});
     }
 
+    let revoke_fn = {
+        let perform_revocation = perform_revocation.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let perform_revocation = perform_revocation.clone();
+            Box::pin(async move { perform_revocation.run() })
+        })
+    };
+
     let highlight_class = match *is_current_session {
         true => Some("border-warning text-warning"),
         false => None,
     };
-    let button_name = match *is_current_session {
-        true => "Revoke (and log out)",
-        false => "Revoke",
-    };
-    let button_class = match *is_current_session {
-        true => "btn-danger",
-        false => "btn-secondary",
+    let rename_fn = {
+        let new_name = new_name.clone();
+        let is_renaming = is_renaming.clone();
+        let refresh_pulse = refresh_pulse.clone();
+        let session_id = props.session_id;
+        let current_session = current_session.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let new_name = new_name.clone();
+            let is_renaming = is_renaming.clone();
+            let refresh_pulse = refresh_pulse.clone();
+            let context_setter = context_setter.clone();
+            let current_session = current_session.clone();
+            Box::pin(async move {
+                let name = Some(new_name.trim().to_string()).filter(|name| !name.is_empty());
+                let request = RenameTokenRequest { name: name.clone() };
+                match auth_rename_token(session_id, request).await {
+                    Ok(ResponseType_auth_rename_token::Status204(_)) => {
+                        is_renaming.set(false);
+                        refresh_pulse.set(!*refresh_pulse);
+                        // Also update the user menu, which shows the name of the current session.
+                        if let (UserContext::LoggedIn(token_data), Some(context_setter)) =
+                            (&*current_session, context_setter)
+                        {
+                            if token_data.token.id == session_id {
+                                let mut token_data = token_data.clone();
+                                token_data.token.name = name;
+                                context_setter.0.emit(UserContext::LoggedIn(token_data));
+                            }
+                        }
+                    }
+                    _ => log::error!("Failed to rename session: {session_id}"),
+                }
+            })
+        })
     };
+
     let result_html = match &*session {
         Ok(ResponseType_auth_get_token::Status200(token_info)) => {
+            let token = &token_info.token;
+            let device = describe_device(token.user_agent.as_deref());
+            let location =
+                describe_location(token.location.as_deref(), token.ip_address.as_deref());
+            // Older sessions don't have a creation time, but their ID still says when they were made.
+            let created = token.created.unwrap_or_else(|| token.id.timestamp());
+            let created = format!("Signed in at: {created}");
+            let last_used = match token.last_used {
+                Some(last_used) => format!("Last active at: {last_used}"),
+                None => "Last active at: unknown".to_string(),
+            };
+            let maybe_current_badge = if *is_current_session {
+                html! { <span class="badge text-bg-warning ms-2">{"This session"}</span> }
+            } else {
+                html! {}
+            };
+            let title_html = if *is_renaming {
+                let oninput = {
+                    let new_name = new_name.clone();
+                    Callback::from(move |event: InputEvent| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        new_name.set(input.value());
+                    })
+                };
+                let cancel = {
+                    let is_renaming = is_renaming.clone();
+                    Callback::from(move |_| is_renaming.set(false))
+                };
+                html! {
+                    <div class="input-group mb-2">
+                        <input type="text" class="form-control" placeholder={device.clone()} value={(*new_name).clone()} {oninput} />
+                        <AsyncButton class="" text="Save" color={crate::components::Color::Primary} onclick_fn={rename_fn} />
+                        <Button class="" text="Cancel" color={crate::components::Color::Secondary} onclick={cancel} />
+                    </div>
+                }
+            } else {
+                let start_renaming = {
+                    let is_renaming = is_renaming.clone();
+                    let new_name = new_name.clone();
+                    let name = token.name.clone().unwrap_or_default();
+                    Callback::from(move |_| {
+                        new_name.set(name.clone());
+                        is_renaming.set(true);
+                    })
+                };
+                html! {
+                    <h5 class="card-title">
+                        {token.name.clone().unwrap_or_else(|| device.clone())}
+                        {maybe_current_badge}
+                        <button type="button" class="btn btn-sm btn-link" onclick={start_renaming}>{"Rename"}</button>
+                    </h5>
+                }
+            };
+            // With a name in the title, the device goes below it.
+            let subtitle = match &token.name {
+                Some(_) => format!("{device}, {location}"),
+                None => location,
+            };
+            let onchange_selected = {
+                let on_toggle = props.on_toggle.clone();
+                let session_id = props.session_id;
+                Callback::from(move |_: Event| on_toggle.emit(session_id))
+            };
+            let is_bulk_revoking = props.revocation == Some(RevocationStatus::InProgress);
+            let maybe_revocation_error = match &props.revocation {
+                Some(RevocationStatus::Failed(e)) => html! {
+                    <p class="card-text text-danger">{format!("Could not revoke this session: {e}")}</p>
+                },
+                _ => html! {},
+            };
             html! {
                 <div class={classes!("card", "mb-3", highlight_class)}>
                     <div class="card-body">
-                        <h5 class="card-title">{token_info.token.id}</h5>
-                        <p class="card-text">{format!("Expires at: {}", token_info.token.expires)}</p>
-                        <button class={classes!("btn", button_class)} onclick={Callback::from(move |_| { perform_revocation.run(); })}>
-                            <LoadingSpinner show={*is_deleting} size={Size::Small} />
-                            {button_name}
-                        </button>
+                        if !*is_current_session {
+                            <div class="form-check float-end">
+                                <input class="form-check-input" type="checkbox" id={format!("select_session_{}", props.session_id)}
+                                    checked={props.selected} disabled={is_bulk_revoking} onchange={onchange_selected} />
+                                <label class="form-check-label" for={format!("select_session_{}", props.session_id)}>{"Select"}</label>
+                            </div>
+                        }
+                        {title_html}
+                        <h6 class="card-subtitle mb-2 text-body-secondary" title={token.user_agent.clone().unwrap_or_default()}>{subtitle}</h6>
+                        <p class="card-text">
+                            {created}<br />
+                            {last_used}<br />
+                            {format!("Expires at: {}", token.expires)}<br />
+                            <small class="text-body-secondary">{format!("Session ID: {}", token.id)}</small>
+                        </p>
+                        {maybe_revocation_error}
+                        if *is_current_session {
+                            <ConfirmButton id={format!("confirm-revoke-session-{}", props.session_id)} class=""
+                                text="Revoke (and log out)" spinning={*is_deleting} onclick_fn={revoke_fn}
+                                title="Revoke this session?" confirm_text="Revoke and log out">
+                                <p>{"This is the session you are using right now, so you will be logged out."}</p>
+                            </ConfirmButton>
+                        } else {
+                            <button class="btn btn-secondary" onclick={Callback::from(move |_| { perform_revocation.run(); })}>
+                                <LoadingSpinner show={*is_deleting || is_bulk_revoking} size={Size::Small} />
+                                {"Revoke"}
+                            </button>
+                        }
                     </div>
                 </div>
             }
@@ -1,138 +1,453 @@
-use std::{future::Future, pin::Pin};
+use std::{future::Future, pin::Pin, rc::Rc};
 
-use api_types::{v1::SleepState, Snowflake};
+use api_types::{
+    v1::{DateTimeUtc, NewSleepRecord, SleepState},
+    Snowflake,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::{prelude::*, suspense::use_future_with_deps};
 use yew_bootstrap::{
     component::{Alert, Button, Modal, ModalBody, ModalFooter, ModalHeader},
     util::Color,
 };
+use yew_hooks::use_async;
+use yew_router::prelude::{use_location, use_navigator};
 
 use crate::{
     api::*,
-    components::{AsyncButton, SleepTimer},
-    utils::get_current_time,
+    components::{AsyncButton, ConfirmButton, LoadingSpinner, Size, SleepTimer},
+    utils::{
+        get_current_time, hide_modal,
+        time::{
+            from_local_input_value, local_day_end, local_day_start, local_today,
+            to_local_input_value,
+        },
+    },
+    Route,
 };
 
+/// Which sleep records to show, by when they happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum DateRangePreset {
+    #[serde(rename = "all")]
+    All,
+    #[serde(rename = "7d")]
+    Last7Days,
+    #[serde(rename = "30d")]
+    Last30Days,
+    #[serde(rename = "month")]
+    ThisMonth,
+    /// Between the `from` and `to` dates in the URL.
+    #[serde(rename = "custom")]
+    Custom,
+}
+
+impl DateRangePreset {
+    const ALL: [DateRangePreset; 5] = [
+        Self::All,
+        Self::Last7Days,
+        Self::Last30Days,
+        Self::ThisMonth,
+        Self::Custom,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::All => "All time",
+            Self::Last7Days => "Last 7 days",
+            Self::Last30Days => "Last 30 days",
+            Self::ThisMonth => "This month",
+            Self::Custom => "Custom range",
+        }
+    }
+}
+
+/// The query parameters of the sleep history page, so that a filtered view can be bookmarked.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+struct SleepHistoryQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    range: Option<DateRangePreset>,
+    /// First day of a custom range, as `2023-05-01`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    /// Last day of a custom range, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to: Option<String>,
+}
+
+impl SleepHistoryQuery {
+    fn preset(&self) -> DateRangePreset {
+        self.range.unwrap_or(DateRangePreset::All)
+    }
+
+    /// The first and last day whose sleep records are shown, given what day it is.
+    /// `None` leaves that side open, which is also what happens to a date that doesn't parse.
+    fn date_range(&self, today: NaiveDate) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        match self.preset() {
+            DateRangePreset::All => (None, None),
+            // Today counts as one of the days.
+            DateRangePreset::Last7Days => (Some(today - Duration::days(6)), None),
+            DateRangePreset::Last30Days => (Some(today - Duration::days(29)), None),
+            DateRangePreset::ThisMonth => (today.with_day(1), None),
+            DateRangePreset::Custom => (
+                self.from.as_deref().and_then(|from| from.parse().ok()),
+                self.to.as_deref().and_then(|to| to.parse().ok()),
+            ),
+        }
+    }
+
+    /// The times between which sleep records are shown. `None` leaves that side open.
+    fn bounds(&self) -> (Option<DateTimeUtc>, Option<DateTimeUtc>) {
+        let (first_day, last_day) = self.date_range(local_today());
+        (
+            first_day.and_then(local_day_start),
+            last_day.and_then(local_day_end),
+        )
+    }
+}
+
+/// The user's sleep records, with a picker for the time range to show.
 #[function_component(SleepList)]
 pub fn sleep_list() -> Html {
-    let fallback = html! {
-        <>
-            <div class="btn-group mb-3" role="group">
-                <button class="btn btn-primary placeholder" disabled={true}>{"Refresh"}</button>
-            </div>
-            <table class="table table-striped table-sm">
-                <thead>
-                    <tr>
-                        <th scope="col">{"Start time"}</th>
-                        <th scope="col">{"End time"}</th>
-                        <th scope="col">{"Duration"}</th>
-                        <th scope="col">{"Actions"}</th>
-                    </tr>
-                </thead>
-                <tbody>
-                    <SleepListFallback />
-                    <SleepListFallback />
-                    <SleepListFallback />
-                </tbody>
-            </table>
-        </>
+    let navigator = use_navigator().unwrap();
+    let query = use_location()
+        .and_then(|location| location.query::<SleepHistoryQuery>().ok())
+        .unwrap_or_default();
+    let preset = query.preset();
+    let (from, to) = query.bounds();
+
+    let set_query = Callback::from(move |query: SleepHistoryQuery| {
+        if let Err(e) = navigator.push_with_query(&Route::DashboardSleepHistory, &query) {
+            log::error!("Failed to update the sleep history URL: {e}");
+        }
+    });
+
+    let onchange_preset = {
+        let query = query.clone();
+        let set_query = set_query.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            let index: usize = select.value().parse().unwrap_or(0);
+            let new_query = match DateRangePreset::ALL[index] {
+                DateRangePreset::All => SleepHistoryQuery::default(),
+                // Start a custom range from the last week, which is easier to adjust than empty inputs.
+                DateRangePreset::Custom => {
+                    let today = local_today();
+                    SleepHistoryQuery {
+                        range: Some(DateRangePreset::Custom),
+                        from: query
+                            .from
+                            .clone()
+                            .or_else(|| Some((today - Duration::days(6)).to_string())),
+                        to: query.to.clone().or_else(|| Some(today.to_string())),
+                    }
+                }
+                other => SleepHistoryQuery {
+                    range: Some(other),
+                    ..Default::default()
+                },
+            };
+            set_query.emit(new_query);
+        })
+    };
+
+    let onchange_from = {
+        let query = query.clone();
+        let set_query = set_query.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let value = input.value();
+            set_query.emit(SleepHistoryQuery {
+                from: (!value.is_empty()).then_some(value),
+                ..query.clone()
+            });
+        })
+    };
+
+    let onchange_to = {
+        let query = query.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let value = input.value();
+            set_query.emit(SleepHistoryQuery {
+                to: (!value.is_empty()).then_some(value),
+                ..query.clone()
+            });
+        })
     };
 
+    let preset_options = DateRangePreset::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, each_preset)| {
+            html! {
+                <option value={index.to_string()} selected={*each_preset == preset}>{each_preset.name()}</option>
+            }
+        })
+        .collect::<Html>();
+
+    let is_range_backwards = matches!((from, to), (Some(from), Some(to)) if from >= to);
+
     html! {
         <>
         <h2>{ "Sleep records" }</h2>
-        <Suspense {fallback}>
-            <SleepListInner />
-        </Suspense>
+        <div class="row g-2 align-items-end mb-3">
+            <div class="col-auto">
+                <label for="sleep-range-preset" class="form-label">{"Show"}</label>
+                <select id="sleep-range-preset" class="form-select" onchange={onchange_preset}>
+                    {preset_options}
+                </select>
+            </div>
+            if preset == DateRangePreset::Custom {
+                <div class="col-auto">
+                    <label for="sleep-range-from" class="form-label">{"From"}</label>
+                    <input type="date" id="sleep-range-from" class={classes!("form-control", is_range_backwards.then_some("is-invalid"))}
+                        value={query.from.clone().unwrap_or_default()} onchange={onchange_from} />
+                </div>
+                <div class="col-auto">
+                    <label for="sleep-range-to" class="form-label">{"To"}</label>
+                    <input type="date" id="sleep-range-to" class={classes!("form-control", is_range_backwards.then_some("is-invalid"))}
+                        value={query.to.clone().unwrap_or_default()} onchange={onchange_to} />
+                </div>
+            }
+        </div>
+        if is_range_backwards {
+            <Alert style={Color::Warning}>{"The start of the range is after its end, so there is nothing to show."}</Alert>
+        }
+        // A new range starts over with its own first page.
+        <SleepListPages key={format!("{from:?}-{to:?}")} {from} {to} />
         </>
     }
 }
 
-#[function_component(SleepListInner)]
-fn sleep_list_inner() -> HtmlResult {
-    let refresh_pulse = use_state(|| false);
-    let refresh_pulse_out = refresh_pulse.clone();
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct SleepListPagesProps {
+    from: Option<DateTimeUtc>,
+    to: Option<DateTimeUtc>,
+}
 
-    let states = use_future_with_deps(
-        |_refresh_pulse_state| async {
-            let res = sleep_get_list().await;
-            res
-        },
-        refresh_pulse_out,
-    );
-    let states = states?;
-
-    let result_html = match &*states {
-        Ok(ResponseType_sleep_get_list::Status200(sleep_states)) => {
-            let mut sleep_states = sleep_states.clone();
-            sleep_states.sort_by(|a, b| {
-                b.end
-                    .or_else(|| Some(get_current_time()))
-                    .unwrap()
-                    .cmp(&a.end.or_else(|| Some(get_current_time())).unwrap())
-            });
-            let sleep_rows = sleep_states
-                .iter()
-                .map(|state| {
-                    let fallback = html!(<SleepListFallback />);
-                    html! {
-                        <Suspense {fallback}>
-                            <SleepListRow sleep_id={state.id} />
-                        </Suspense>
-                    }
-                })
-                .collect::<Html>();
+/// The sleep records loaded so far. Edits and deletions are applied here as well,
+/// so that changed records are checked for overlaps with the current times.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SleepRecords {
+    records: Vec<SleepState>,
+}
 
-            html! {
-                <>
-                    <div class="btn-group mb-3" role="group">
-                        <button class="btn btn-primary" onclick={Callback::from(move |_| {
-                            refresh_pulse.set(!*refresh_pulse);
-                        })}>
-                            { "Refresh" }
-                        </button>
-                    </div>
+enum SleepRecordsAction {
+    /// Forget all records, to start over from the first page.
+    Reset,
+    /// Add the next page of older records.
+    Append(Vec<SleepState>),
+    Update(SleepState),
+    Remove(Snowflake),
+}
 
+impl Reducible for SleepRecords {
+    type Action = SleepRecordsAction;
 
-                    <table class="table table-striped table-sm">
-                        <thead>
-                            <tr>
-                                <th scope="col">{"Start time"}</th>
-                                <th scope="col">{"End time"}</th>
-                                <th scope="col">{"Duration"}</th>
-                                <th scope="col">{"Actions"}</th>
-                            </tr>
-                        </thead>
-                        <tbody>
-                            { sleep_rows }
-                        </tbody>
-                    </table>
-                </>
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut next = (*self).clone();
+        match action {
+            SleepRecordsAction::Reset => next.records.clear(),
+            SleepRecordsAction::Append(records) => next.records.extend(records),
+            SleepRecordsAction::Update(state) => {
+                if let Some(record) = next.records.iter_mut().find(|record| record.id == state.id) {
+                    *record = state;
+                }
+                // A new start time may move the record; the list is sorted like the API sorts it.
+                next.records.sort_by(|a, b| b.start.cmp(&a.start));
             }
+            SleepRecordsAction::Remove(id) => next.records.retain(|record| record.id != id),
         }
-        _ => {
+        Rc::new(next)
+    }
+}
+
+/// The sleep records in a time range, newest first.
+///
+/// Records are loaded a page at a time, so that users with years of history don't have to wait for all of it.
+#[function_component(SleepListPages)]
+fn sleep_list_pages(props: &SleepListPagesProps) -> Html {
+    let from = props.from;
+    let to = props.to;
+    let records = use_reducer(SleepRecords::default);
+    // Start time of the oldest record loaded so far.
+    let next_cursor = use_state(|| None::<DateTimeUtc>);
+    let is_finished = use_state(|| false);
+    let refresh_pulse = use_state(|| false);
+
+    let load_page = {
+        let dispatcher = records.dispatcher();
+        let next_cursor = next_cursor.clone();
+        let is_finished = is_finished.clone();
+        use_async(async move {
+            let query = SleepListQuery {
+                before: *next_cursor,
+                from,
+                to,
+            };
+            match sleep_get_list(query).await {
+                Ok(ResponseType_sleep_get_list::Status200(page)) => {
+                    dispatcher.dispatch(SleepRecordsAction::Append(page.records));
+                    is_finished.set(page.next_cursor.is_none());
+                    next_cursor.set(page.next_cursor);
+                    Ok(())
+                }
+                Ok(ResponseType_sleep_get_list::Status404(_)) => {
+                    is_finished.set(true);
+                    Ok(())
+                }
+                Err(e) => {
+                    log::error!("Failed to load sleep records: {e}");
+                    Err(())
+                }
+            }
+        })
+    };
+
+    {
+        // Runs on the first render and again on every refresh, which starts over from the newest page.
+        let load_page = load_page.clone();
+        use_effect_with_deps(move |_| load_page.run(), *refresh_pulse);
+    }
+
+    let refresh = {
+        let dispatcher = records.dispatcher();
+        let next_cursor = next_cursor.clone();
+        let is_finished = is_finished.clone();
+        Callback::from(move |_: ()| {
+            dispatcher.dispatch(SleepRecordsAction::Reset);
+            next_cursor.set(None);
+            is_finished.set(false);
+            refresh_pulse.set(!*refresh_pulse);
+        })
+    };
+
+    // Edits and new records are only checked against the records loaded so far;
+    // the API still rejects overlaps with older ones.
+    let all_records = Rc::new(records.records.clone());
+    let on_changed = {
+        let dispatcher = records.dispatcher();
+        Callback::from(move |state: SleepState| {
+            dispatcher.dispatch(SleepRecordsAction::Update(state))
+        })
+    };
+    let on_deleted = {
+        let dispatcher = records.dispatcher();
+        Callback::from(move |id: Snowflake| dispatcher.dispatch(SleepRecordsAction::Remove(id)))
+    };
+    let sleep_rows = records
+        .records
+        .iter()
+        .map(|state| {
+            let fallback = html!(<SleepListFallback />);
             html! {
-                <Alert style={Color::Danger}>{"Failed to load your sleep records. Try reloading the page."}</Alert>
+                <Suspense key={state.id.to_string()} {fallback}>
+                    <SleepListRow sleep_id={state.id} all_records={all_records.clone()}
+                        on_changed={on_changed.clone()} on_deleted={on_deleted.clone()} />
+                </Suspense>
             }
+        })
+        .collect::<Html>();
+
+    let is_first_page_loading = load_page.loading && records.records.is_empty();
+    let maybe_placeholder_rows = if is_first_page_loading {
+        html! {
+            <>
+                <SleepListFallback />
+                <SleepListFallback />
+                <SleepListFallback />
+            </>
         }
+    } else {
+        html! {}
     };
 
-    Ok(result_html)
+    let maybe_error_alert = if load_page.error.is_some() {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to load your sleep records. Try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    let footer = if load_page.loading && !records.records.is_empty() {
+        html! {
+            <p><LoadingSpinner show={true} size={Size::Small} />{"Loading older records..."}</p>
+        }
+    } else if records.records.is_empty() && *is_finished && (from.is_some() || to.is_some()) {
+        html! { <p>{"There are no sleep records in this time range."}</p> }
+    } else if records.records.is_empty() && *is_finished {
+        html! { <p>{"You haven't recorded any sleep yet."}</p> }
+    } else if !*is_finished && !is_first_page_loading {
+        let onclick = {
+            let load_page = load_page.clone();
+            Callback::from(move |_| load_page.run())
+        };
+        html! {
+            <button class="btn btn-secondary mb-3" {onclick}>{"Load older records"}</button>
+        }
+    } else {
+        html! {}
+    };
+
+    let onclick_refresh = {
+        let refresh = refresh.clone();
+        Callback::from(move |_| refresh.emit(()))
+    };
+
+    html! {
+        <>
+        <div class="btn-group mb-3" role="group">
+            <button class="btn btn-primary" disabled={is_first_page_loading} onclick={onclick_refresh}>
+                { "Refresh" }
+            </button>
+            <AddSleepRecord all_records={all_records} on_added={refresh} />
+        </div>
+
+        <table class="table table-striped table-sm">
+            <thead>
+                <tr>
+                    <th scope="col">{"Start time"}</th>
+                    <th scope="col">{"End time"}</th>
+                    <th scope="col">{"Duration"}</th>
+                    <th scope="col">{"Actions"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { sleep_rows }
+                { maybe_placeholder_rows }
+            </tbody>
+        </table>
+        {maybe_error_alert}
+        {footer}
+        </>
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Properties)]
 struct SleepListRowProps {
     sleep_id: Snowflake,
+    /// Every record in the list, to check edits for overlaps.
+    all_records: Rc<Vec<SleepState>>,
+    /// Called with the new record after it has been edited.
+    on_changed: Callback<SleepState>,
+    /// Called after the record has been deleted.
+    on_deleted: Callback<Snowflake>,
 }
 
 #[function_component(SleepListRow)]
 fn sleep_list_row(props: &SleepListRowProps) -> HtmlResult {
+    let refresh_pulse = use_state(|| false);
     let sleep = use_future_with_deps(
-        move |id| async move {
-            let res = sleep_get_by_id(*id).await;
+        move |deps| async move {
+            let (id, _refresh_pulse) = *deps;
+            let res = sleep_get_by_id(id).await;
             res
         },
-        props.sleep_id,
+        (props.sleep_id, *refresh_pulse),
     );
     let sleep = sleep?;
 
@@ -142,9 +457,11 @@ fn sleep_list_row(props: &SleepListRowProps) -> HtmlResult {
     let delete_fn = {
         let is_hidden = is_hidden.clone();
         let token_id = token_id.clone();
+        let on_deleted = props.on_deleted.clone();
         Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
             let is_hidden = is_hidden.clone();
             let token_id = token_id.clone();
+            let on_deleted = on_deleted.clone();
             Box::pin(async move {
                 let outcome = match sleep_delete_by_id(*token_id).await {
                     Ok(v) => match v {
@@ -154,6 +471,9 @@ fn sleep_list_row(props: &SleepListRowProps) -> HtmlResult {
                     Err(_) => false,
                 };
                 is_hidden.set(outcome);
+                if outcome {
+                    on_deleted.emit(*token_id);
+                }
             })
         })
     };
@@ -162,6 +482,14 @@ fn sleep_list_row(props: &SleepListRowProps) -> HtmlResult {
         return Ok(html!());
     }
 
+    let on_saved = {
+        let on_changed = props.on_changed.clone();
+        Callback::from(move |state: SleepState| {
+            refresh_pulse.set(!*refresh_pulse);
+            on_changed.emit(state);
+        })
+    };
+
     let result_html = match &*sleep {
         Ok(ResponseType_sleep_get_by_id::Status200(state)) => {
             let duration = match state.end {
@@ -195,8 +523,11 @@ fn sleep_list_row(props: &SleepListRowProps) -> HtmlResult {
                     <td>{state.start}</td>
                     {end_times}
                     <td>
-                        <AsyncButton class="" text="Delete" color={crate::components::Color::Danger} onclick_fn={delete_fn} />
-                        <SleepListRowEdit sleep_state={state.clone()}/>
+                        <ConfirmButton id={format!("confirm-delete-sleep-{}", state.id)} class="" text="Delete"
+                            onclick_fn={delete_fn} title="Delete this sleep record?" confirm_text="Delete">
+                            <p>{format!("The sleep record starting at {} will be deleted permanently.", state.start)}</p>
+                        </ConfirmButton>
+                        <SleepListRowEdit sleep_state={state.clone()} all_records={props.all_records.clone()} on_saved={on_saved} />
                     </td>
                 </tr>
             }
@@ -234,25 +565,523 @@ fn sleep_list_fallback() -> Html {
     }
 }
 
+/// The start and end of every record except `except`, which is the record being edited.
+fn times_of_other_records(
+    records: &[SleepState],
+    except: Option<Snowflake>,
+) -> Vec<(DateTimeUtc, Option<DateTimeUtc>)> {
+    records
+        .iter()
+        .filter(|record| Some(record.id) != except)
+        .map(|record| (record.start, record.end))
+        .collect()
+}
+
+/// Check new times for a sleep record, returning what is wrong with them as sentences to show the user.
+///
+/// `end` may only be missing if the record is the sleep that is still going on.
+/// `others` are the start and end of the other records, which the new times must not overlap.
+fn validate_sleep_times(
+    start: Option<DateTimeUtc>,
+    end: Option<DateTimeUtc>,
+    is_ongoing: bool,
+    others: &[(DateTimeUtc, Option<DateTimeUtc>)],
+    now: DateTimeUtc,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let start = match start {
+        Some(start) => start,
+        None => return vec!["Enter a valid start time.".to_string()],
+    };
+    if start > now {
+        problems.push("The start can't be in the future.".to_string());
+    }
+    match end {
+        Some(end) if end <= start => problems.push("The end must be after the start.".to_string()),
+        Some(end) if end > now => problems.push("The end can't be in the future.".to_string()),
+        Some(_) => (),
+        None if is_ongoing => (),
+        None => problems.push("Enter a valid end time.".to_string()),
+    }
+
+    // A sleep that is still going on lasts until now.
+    let end = end.unwrap_or(now);
+    for (other_start, other_end) in others {
+        let other_end = other_end.unwrap_or(now);
+        if start < other_end && *other_start < end {
+            problems.push(format!(
+                "This overlaps with the sleep from {other_start} to {other_end}."
+            ));
+        }
+    }
+    problems
+}
+
 #[derive(Clone, Debug, PartialEq, Properties)]
 struct SleepListRowEditProps {
     sleep_state: SleepState,
+    all_records: Rc<Vec<SleepState>>,
+    /// Called with the new record after the changes have been saved.
+    on_saved: Callback<SleepState>,
 }
 
 #[function_component(SleepListRowEdit)]
 fn sleep_list_row_edit(props: &SleepListRowEditProps) -> Html {
     let id = format!("sleep_edit-{}", props.sleep_state.id.to_string());
+    let start_input = use_state(|| to_local_input_value(props.sleep_state.start));
+    let end_input = use_state(|| {
+        props
+            .sleep_state
+            .end
+            .map(to_local_input_value)
+            .unwrap_or_default()
+    });
+    let is_save_error = use_state(|| false);
+
+    // Start over from the saved record whenever it changes.
+    {
+        let start_input = start_input.clone();
+        let end_input = end_input.clone();
+        use_effect_with_deps(
+            move |sleep_state| {
+                start_input.set(to_local_input_value(sleep_state.start));
+                end_input.set(
+                    sleep_state
+                        .end
+                        .map(to_local_input_value)
+                        .unwrap_or_default(),
+                );
+            },
+            props.sleep_state.clone(),
+        );
+    }
+
+    // The inputs only have minute precision, so keep the original time for a field that wasn't touched.
+    let original = &props.sleep_state;
+    let start = if *start_input == to_local_input_value(original.start) {
+        Some(original.start)
+    } else {
+        from_local_input_value(&start_input)
+    };
+    let end = match original.end {
+        Some(original_end) if *end_input == to_local_input_value(original_end) => {
+            Some(original_end)
+        }
+        _ => from_local_input_value(&end_input),
+    };
+    let is_ongoing = original.end.is_none();
+    let others = times_of_other_records(&props.all_records, Some(original.id));
+    let problems = validate_sleep_times(start, end, is_ongoing, &others, get_current_time());
+
+    let oninput_start = {
+        let start_input = start_input.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            start_input.set(input.value());
+        })
+    };
+
+    let oninput_end = {
+        let end_input = end_input.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            end_input.set(input.value());
+        })
+    };
+
+    let save_fn = {
+        let is_save_error = is_save_error.clone();
+        let on_saved = props.on_saved.clone();
+        let modal_id = id.clone();
+        let mut new_state = original.clone();
+        if let Some(start) = start {
+            new_state.start = start;
+        }
+        new_state.end = end;
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let is_save_error = is_save_error.clone();
+            let on_saved = on_saved.clone();
+            let modal_id = modal_id.clone();
+            let new_state = new_state.clone();
+            Box::pin(async move {
+                match sleep_put_by_id(new_state.id, new_state.clone()).await {
+                    Ok(ResponseType_sleep_put_by_id::Status204(_)) => {
+                        is_save_error.set(false);
+                        #[allow(unused_unsafe)]
+                        unsafe {
+                            hide_modal(&modal_id)
+                        };
+                        on_saved.emit(new_state);
+                    }
+                    _ => {
+                        log::error!("Failed to save sleep record: {}", new_state.id);
+                        is_save_error.set(true);
+                    }
+                }
+            })
+        })
+    };
+
+    let problem_items = problems
+        .iter()
+        .map(|problem| html! { <li>{problem}</li> })
+        .collect::<Html>();
+    let maybe_problems = if problems.is_empty() {
+        html! {}
+    } else {
+        html! { <ul class="text-danger small mb-0">{problem_items}</ul> }
+    };
+
+    let maybe_error_alert = if *is_save_error {
+        html! {
+            <Alert style={Color::Danger}>{"Failed to save the sleep record. Try again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    let end_label = if is_ongoing {
+        "End (leave empty if you are still sleeping)"
+    } else {
+        "End"
+    };
+
     html!(
         <>
             <Modal id={id.clone()}>
                 <ModalHeader title="Editing sleep record" id={id.clone()} />
                 <ModalBody>
-                    <p>{"TODO"}</p>
+                    {maybe_error_alert}
+                    <div class="mb-3">
+                        <label for={format!("{id}-start")} class="form-label">{"Start"}</label>
+                        <input type="datetime-local" class="form-control" id={format!("{id}-start")}
+                            value={(*start_input).clone()} oninput={oninput_start} />
+                    </div>
+                    <div class="mb-3">
+                        <label for={format!("{id}-end")} class="form-label">{end_label}</label>
+                        <input type="datetime-local" class="form-control" id={format!("{id}-end")}
+                            value={(*end_input).clone()} oninput={oninput_end} />
+                    </div>
+                    <p class="text-body-secondary small">{"Times are in your browser's timezone."}</p>
+                    {maybe_problems}
                 </ModalBody>
                 <ModalFooter>
+                    <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">{"Cancel"}</button>
+                    <AsyncButton class="" text="Save" color={crate::components::Color::Primary} onclick_fn={save_fn} enabled={problems.is_empty()} />
                 </ModalFooter>
             </Modal>
             <Button modal_target={id.clone()}>{"Edit"}</Button>
         </>
     )
 }
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+struct AddSleepRecordProps {
+    all_records: Rc<Vec<SleepState>>,
+    /// Called after the record has been created.
+    on_added: Callback<()>,
+}
+
+/// Button and modal for adding a finished sleep after the fact, e.g. one the user forgot to track.
+#[function_component(AddSleepRecord)]
+fn add_sleep_record(props: &AddSleepRecordProps) -> Html {
+    let id = "sleep_add".to_string();
+    let start_input = use_state(String::new);
+    let end_input = use_state(String::new);
+    let save_error = use_state(|| None::<&'static str>);
+
+    let start = from_local_input_value(&start_input);
+    let end = from_local_input_value(&end_input);
+    // Only complain once the user has entered something.
+    let problems = if start_input.is_empty() && end_input.is_empty() {
+        Vec::new()
+    } else {
+        let others = times_of_other_records(&props.all_records, None);
+        validate_sleep_times(start, end, false, &others, get_current_time())
+    };
+    let is_complete = start.is_some() && end.is_some() && problems.is_empty();
+
+    let oninput_start = {
+        let start_input = start_input.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            start_input.set(input.value());
+        })
+    };
+
+    let oninput_end = {
+        let end_input = end_input.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            end_input.set(input.value());
+        })
+    };
+
+    let save_fn = {
+        let start_input = start_input.clone();
+        let end_input = end_input.clone();
+        let save_error = save_error.clone();
+        let on_added = props.on_added.clone();
+        let modal_id = id.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let start_input = start_input.clone();
+            let end_input = end_input.clone();
+            let save_error = save_error.clone();
+            let on_added = on_added.clone();
+            let modal_id = modal_id.clone();
+            Box::pin(async move {
+                let (start, end) = match (start, end) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => return,
+                };
+                match sleep_create(NewSleepRecord { start, end }).await {
+                    Ok(ResponseType_sleep_create::Status201(state)) => {
+                        log::info!("Added sleep record: {}", state.id);
+                        save_error.set(None);
+                        start_input.set(String::new());
+                        end_input.set(String::new());
+                        #[allow(unused_unsafe)]
+                        unsafe {
+                            hide_modal(&modal_id)
+                        };
+                        on_added.emit(());
+                    }
+                    Ok(ResponseType_sleep_create::Status409(_)) => {
+                        save_error.set(Some("This overlaps with another sleep record."));
+                    }
+                    Err(e) => {
+                        log::error!("Failed to add sleep record: {e}");
+                        save_error.set(Some("Failed to add the sleep record. Try again."));
+                    }
+                }
+            })
+        })
+    };
+
+    let problem_items = problems
+        .iter()
+        .map(|problem| html! { <li>{problem}</li> })
+        .collect::<Html>();
+    let maybe_problems = if problems.is_empty() {
+        html! {}
+    } else {
+        html! { <ul class="text-danger small mb-0">{problem_items}</ul> }
+    };
+
+    let maybe_error_alert = match *save_error {
+        Some(message) => html! {
+            <Alert style={Color::Danger}>{message}</Alert>
+        },
+        None => html! {},
+    };
+
+    html!(
+        <>
+            <Modal id={id.clone()}>
+                <ModalHeader title="Adding a sleep record" id={id.clone()} />
+                <ModalBody>
+                    {maybe_error_alert}
+                    <div class="mb-3">
+                        <label for="sleep_add-start" class="form-label">{"Went to sleep"}</label>
+                        <input type="datetime-local" class="form-control" id="sleep_add-start"
+                            value={(*start_input).clone()} oninput={oninput_start} />
+                    </div>
+                    <div class="mb-3">
+                        <label for="sleep_add-end" class="form-label">{"Woke up"}</label>
+                        <input type="datetime-local" class="form-control" id="sleep_add-end"
+                            value={(*end_input).clone()} oninput={oninput_end} />
+                    </div>
+                    <p class="text-body-secondary small">{"Times are in your browser's timezone."}</p>
+                    {maybe_problems}
+                </ModalBody>
+                <ModalFooter>
+                    <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">{"Cancel"}</button>
+                    <AsyncButton class="" text="Add" color={crate::components::Color::Primary} onclick_fn={save_fn} enabled={is_complete} />
+                </ModalFooter>
+            </Modal>
+            <Button style={Color::Secondary} modal_target={id.clone()}>{"Add record"}</Button>
+        </>
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    /// A time this many hours after an arbitrary point.
+    fn hours(hours: u64) -> DateTimeUtc {
+        DateTimeUtc::from(
+            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000 + hours * 3600),
+        )
+    }
+
+    #[test]
+    fn accepts_a_finished_sleep() {
+        assert!(
+            validate_sleep_times(Some(hours(0)), Some(hours(8)), false, &[], hours(24)).is_empty()
+        );
+    }
+
+    #[test]
+    fn requires_a_start() {
+        assert_eq!(
+            validate_sleep_times(None, Some(hours(8)), false, &[], hours(24)),
+            vec!["Enter a valid start time.".to_string()]
+        );
+    }
+
+    #[test]
+    fn requires_an_end_unless_ongoing() {
+        assert_eq!(
+            validate_sleep_times(Some(hours(0)), None, false, &[], hours(24)),
+            vec!["Enter a valid end time.".to_string()]
+        );
+        assert!(validate_sleep_times(Some(hours(0)), None, true, &[], hours(24)).is_empty());
+    }
+
+    #[test]
+    fn rejects_ends_before_starts() {
+        assert_eq!(
+            validate_sleep_times(Some(hours(8)), Some(hours(8)), false, &[], hours(24)),
+            vec!["The end must be after the start.".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_times_in_the_future() {
+        assert_eq!(
+            validate_sleep_times(Some(hours(20)), Some(hours(30)), false, &[], hours(24)),
+            vec!["The end can't be in the future.".to_string()]
+        );
+        assert_eq!(
+            validate_sleep_times(Some(hours(25)), None, true, &[], hours(24)),
+            vec!["The start can't be in the future.".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_overlaps() {
+        let others = [(hours(0), Some(hours(8)))];
+        assert_eq!(
+            validate_sleep_times(Some(hours(7)), Some(hours(9)), false, &others, hours(24)).len(),
+            1
+        );
+        assert_eq!(
+            validate_sleep_times(Some(hours(1)), Some(hours(2)), false, &others, hours(24)).len(),
+            1
+        );
+        // Touching is fine.
+        assert!(
+            validate_sleep_times(Some(hours(8)), Some(hours(10)), false, &others, hours(24))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn an_ongoing_sleep_lasts_until_now() {
+        let others = [(hours(20), None)];
+        assert_eq!(
+            validate_sleep_times(Some(hours(22)), Some(hours(23)), false, &others, hours(24)).len(),
+            1
+        );
+        assert!(
+            validate_sleep_times(Some(hours(10)), Some(hours(20)), false, &others, hours(24))
+                .is_empty()
+        );
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn query_for(range: DateRangePreset) -> SleepHistoryQuery {
+        SleepHistoryQuery {
+            range: Some(range),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn shows_everything_by_default() {
+        let today = date("2023-05-17");
+        assert_eq!(SleepHistoryQuery::default().date_range(today), (None, None));
+        assert_eq!(
+            query_for(DateRangePreset::All).date_range(today),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn presets_count_today_as_one_of_the_days() {
+        let today = date("2023-03-02");
+        assert_eq!(
+            query_for(DateRangePreset::Last7Days).date_range(today),
+            (Some(date("2023-02-24")), None)
+        );
+        assert_eq!(
+            query_for(DateRangePreset::Last30Days).date_range(today),
+            (Some(date("2023-02-01")), None)
+        );
+        assert_eq!(
+            query_for(DateRangePreset::ThisMonth).date_range(today),
+            (Some(date("2023-03-01")), None)
+        );
+    }
+
+    #[test]
+    fn custom_ranges_use_the_dates_from_the_url() {
+        let query = SleepHistoryQuery {
+            range: Some(DateRangePreset::Custom),
+            from: Some("2023-04-01".to_string()),
+            to: Some("2023-04-30".to_string()),
+        };
+        assert_eq!(
+            query.date_range(date("2023-05-17")),
+            (Some(date("2023-04-01")), Some(date("2023-04-30")))
+        );
+    }
+
+    #[test]
+    fn invalid_custom_dates_leave_the_range_open() {
+        let query = SleepHistoryQuery {
+            range: Some(DateRangePreset::Custom),
+            from: Some("2023-02-30".to_string()),
+            to: Some("yesterday".to_string()),
+        };
+        assert_eq!(query.date_range(date("2023-05-17")), (None, None));
+    }
+
+    #[test]
+    fn custom_dates_are_ignored_for_presets() {
+        let query = SleepHistoryQuery {
+            range: Some(DateRangePreset::Last7Days),
+            from: Some("2023-04-01".to_string()),
+            to: Some("2023-04-30".to_string()),
+        };
+        assert_eq!(
+            query.date_range(date("2023-05-17")),
+            (Some(date("2023-05-11")), None)
+        );
+    }
+
+    #[test]
+    fn queries_round_trip_through_the_url() {
+        let query = SleepHistoryQuery {
+            range: Some(DateRangePreset::Custom),
+            from: Some("2023-04-01".to_string()),
+            to: None,
+        };
+        let encoded = serde_urlencoded::to_string(&query).unwrap();
+        assert_eq!(encoded, "range=custom&from=2023-04-01");
+        assert_eq!(
+            serde_urlencoded::from_str::<SleepHistoryQuery>(&encoded).unwrap(),
+            query
+        );
+        assert_eq!(
+            serde_urlencoded::from_str::<SleepHistoryQuery>("").unwrap(),
+            SleepHistoryQuery::default()
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::{future::Future, pin::Pin};
 
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
-use yew_hooks::use_async;
+use yew_hooks::{use_async, use_debounce};
 
 #[derive(Properties, PartialEq)]
 pub struct AsyncTextBoxProps {
@@ -18,13 +18,28 @@ pub struct AsyncTextBoxProps {
     #[prop_or("".into())]
     pub prefill: AttrValue,
 
-    pub onchange_fn: Callback<(), Pin<Box<dyn Future<Output = AsyncTextBoxValidationResult>>>>,
+    /// Validates the given value.
+    pub onchange_fn: Callback<String, Pin<Box<dyn Future<Output = AsyncTextBoxValidationResult>>>>,
+
+    /// Called with the new value on every keystroke, before it is validated.
+    #[prop_or_default]
+    pub oninput: Callback<String>,
+
+    /// Called with the result of each validation, once it is done.
+    #[prop_or_default]
+    pub onvalidated: Callback<AsyncTextBoxValidationResult>,
+
+    /// If set, also validate while the user is typing,
+    /// once they have stopped for this many milliseconds.
+    /// Otherwise, validation only happens when the input loses focus.
+    #[prop_or_default]
+    pub debounce_ms: Option<u32>,
 
     #[prop_or(false)]
     pub disabled: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AsyncTextBoxValidationResult {
     /// The value the user entered is okay, and has been saved.
     Ok,
@@ -38,35 +53,64 @@ pub enum AsyncTextBoxValidationResult {
 #[function_component(AsyncTextBox)]
 pub fn form_text_box(props: &AsyncTextBoxProps) -> Html {
     let content = use_state(|| String::new());
+    // The value as of the latest keystroke, to discard results for values the user has since changed.
+    let latest_content = use_mut_ref(String::new);
     {
         let content = content.clone();
+        let latest_content = latest_content.clone();
         use_effect_with_deps(
-            move |prefill| content.set(prefill.to_string()),
+            move |prefill| {
+                content.set(prefill.to_string());
+                *latest_content.borrow_mut() = prefill.to_string();
+            },
             props.prefill.clone(),
         )
     }
 
-    let onchange = props.onchange_fn.emit(());
+    let onchange = props.onchange_fn.emit((*content).clone());
     let state = use_state(|| Some(AsyncTextBoxValidationResult::Ok));
 
     let my_fut = {
         let state = state.clone();
+        let checked_content = (*content).clone();
+        let latest_content = latest_content.clone();
+        let onvalidated = props.onvalidated.clone();
         async move {
             let res = onchange.await;
-            state.set(Some(res.clone()));
+            if *latest_content.borrow() == checked_content {
+                state.set(Some(res.clone()));
+                onvalidated.emit(res.clone());
+            }
             Ok::<AsyncTextBoxValidationResult, ()>(res)
         }
     };
 
     let action = use_async(my_fut);
 
+    let debounce = {
+        let action = action.clone();
+        use_debounce(
+            move || action.run(),
+            props.debounce_ms.unwrap_or_default(),
+        )
+    };
+
     let oninput_cb = {
         let state = state.clone();
+        let content = content.clone();
+        let oninput = props.oninput.clone();
+        let is_debounced = props.debounce_ms.is_some();
         Callback::from(move |e: InputEvent| {
             e.prevent_default();
             state.set(None);
             let input: HtmlInputElement = e.target_unchecked_into();
-            input.value();
+            let value = input.value();
+            *latest_content.borrow_mut() = value.clone();
+            content.set(value.clone());
+            oninput.emit(value);
+            if is_debounced {
+                debounce.run();
+            }
         })
     };
 
@@ -100,6 +144,7 @@ pub fn form_text_box(props: &AsyncTextBoxProps) -> Html {
             class={item_classes} id={props.id.clone()} value={(*content).clone()}
             style="background-color: var(--bs-body-bg);"  // BUGFIX for Bootstrap 5.2.0 dark mode inputs
             oninput={oninput_cb} onchange={onchange_cb}
+            disabled={props.disabled}
             placeholder={props.label.clone()} />
             <label for={props.id.clone()}>{props.label.clone()}</label>
             {validation_label}
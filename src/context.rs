@@ -1,2 +1,4 @@
 mod user_ctx;
 pub use user_ctx::*;
+mod user_settings_ctx;
+pub use user_settings_ctx::*;
@@ -1,7 +1,13 @@
 use api_types::v1::TokenData;
+use yew::Callback;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum UserContext {
     LoggedIn(TokenData),
     LoggedOut,
 }
+
+/// Provided next to the [`UserContext`] so that components below it can replace it,
+/// for example after the user re-authenticates.
+#[derive(Clone, PartialEq)]
+pub struct UserContextSetter(pub Callback<UserContext>);
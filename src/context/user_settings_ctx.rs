@@ -0,0 +1,12 @@
+use api_types::v1::UserSettings;
+use yew::Callback;
+
+/// The user's settings, as loaded by the dashboard.
+#[derive(Clone, PartialEq)]
+pub struct UserSettingsContext {
+    /// `None` until the settings have arrived from the API.
+    pub settings: Option<UserSettings>,
+
+    /// Replace the settings in the context after they have been saved to the API.
+    pub set: Callback<UserSettings>,
+}
@@ -12,6 +12,12 @@ mod register;
 use register::Register;
 mod confirm_register;
 use confirm_register::ConfirmRegister;
+mod magic_link_login;
+use magic_link_login::MagicLinkLogin;
+mod oidc_callback;
+#[cfg(debug_assertions)]
+use oidc_callback::MockOidcProvider;
+use oidc_callback::OidcCallback;
 
 use crate::components::BigError;
 
@@ -24,6 +30,14 @@ pub enum Route {
 
     #[at("/login")]
     Login,
+    #[at("/login/link/:id/:token")]
+    MagicLinkLogin { id: Snowflake, token: String },
+    #[at("/auth/callback")]
+    OidcCallback,
+    /// Only exists in debug builds, for trying out OpenID Connect without a real provider.
+    #[cfg(debug_assertions)]
+    #[at("/auth/mock-provider")]
+    MockOidcProvider,
     #[at("/dashboard")]
     DashboardHome,
 
@@ -36,6 +50,8 @@ pub enum Route {
 
     #[at("/registration/new")]
     Register,
+    #[at("/registration/invite/:code")]
+    RegisterWithInvite { code: String },
     #[at("/registration/:id/confirm")]
     ConfirmRegister { id: Snowflake },
 
@@ -51,6 +67,10 @@ pub fn switch(routes: Route) -> Html {
             <Secure />
         },
         Route::Login => html! { <Login /> },
+        Route::MagicLinkLogin { id, token } => html! { <MagicLinkLogin link_id={id} {token} /> },
+        Route::OidcCallback => html! { <OidcCallback /> },
+        #[cfg(debug_assertions)]
+        Route::MockOidcProvider => html! { <MockOidcProvider /> },
         Route::DashboardHome => html! { <Dashboard /> },
 
         Route::DashboardSleepHistory => html! { <Dashboard /> },
@@ -58,6 +78,7 @@ pub fn switch(routes: Route) -> Html {
         Route::DashboardProfile => html! { <Dashboard /> },
 
         Route::Register => html! { <Register /> },
+        Route::RegisterWithInvite { code } => html! { <Register invite_code={Some(AttrValue::from(code))} /> },
         Route::ConfirmRegister { id } => html! { <ConfirmRegister registration_id={id} /> },
 
         Route::NotFound => html! { <h1>{ "404" }</h1> },
@@ -1,22 +1,32 @@
-use api_types::v1::{ConfirmRegistrationRequest, ConfirmRegistrationResponse};
+use std::{future::Future, pin::Pin};
+
+use api_types::v1::{ConfirmRegistrationRequest, ConfirmRegistrationResponse, PendingRegistration};
 use api_types::Snowflake;
+use serde::Deserialize;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 use yew::suspense::use_future;
 use yew_bootstrap::component::Alert;
 use yew_bootstrap::util::Color;
-use yew_hooks::use_async;
+use yew_hooks::{use_async, use_interval, use_update};
 use yew_router::prelude::*;
 
 use crate::api::registration_confirm;
 use crate::{api, Route};
 
 use crate::components::style::Size;
+use crate::components::AsyncButton;
 use crate::components::CenteredBox;
 use crate::components::FormSubmitBtn;
 use crate::components::FormTextBox;
 use crate::components::LoadingSpinner;
+use crate::utils::auth_events::{broadcast_auth_event, AuthEvent};
+use crate::utils::time::format_seconds_hms;
+use crate::utils::{get_current_time, get_unix_timestamp};
+
+/// How long to wait between requests for a new confirmation email, in seconds.
+const RESEND_COOLDOWN_SECONDS: f64 = 60.0;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct ConfirmRegisterProps {
@@ -43,6 +53,23 @@ struct TokenInfo {
     pub token: String,
 }
 
+/// Why confirming the token failed; each one needs a different next step from the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfirmError {
+    /// The server could not be reached, or answered unexpectedly.
+    Network,
+    DatabaseError,
+    /// The token is wrong or has expired.
+    RegistrationConfirmError,
+    UserAlreadyExists,
+}
+
+/// The query parameters of the link in the confirmation email.
+#[derive(Deserialize, Clone, Debug, Default)]
+struct ConfirmQuery {
+    token: Option<String>,
+}
+
 #[function_component(ConfirmInner)]
 fn confirm_inner(props: &ConfirmRegisterProps) -> HtmlResult {
     let navigator = use_navigator().unwrap();
@@ -53,92 +80,249 @@ fn confirm_inner(props: &ConfirmRegisterProps) -> HtmlResult {
     })?;
 
     let state_handle = registration_result.as_ref();
-    let token_info = use_state(TokenInfo::default);
-    let is_token_error = use_state(|| false);
+    // If the user followed the link from the email, the token is already in the URL.
+    let token_from_link = use_location()
+        .and_then(|location| location.query::<ConfirmQuery>().ok())
+        .and_then(|query| query.token);
+    let token_info = {
+        let token_from_link = token_from_link.clone();
+        use_state(move || TokenInfo {
+            token: token_from_link.unwrap_or_default(),
+        })
+    };
+    let confirm_error = use_state(|| None::<ConfirmError>);
     let is_button_enabled = use_state(|| true);
     let is_confirming = use_state(|| false);
 
     let confirm_token = {
         let token_info = token_info.clone();
         let is_confirming = is_confirming.clone();
-        let is_token_error = is_token_error.clone();
+        let confirm_error = confirm_error.clone();
         use_async(async move {
             is_confirming.set(true);
-            is_token_error.set(false);
+            confirm_error.set(None);
 
             let request = ConfirmRegistrationRequest {
                 token: token_info.token.clone(),
             };
             let response = registration_confirm(registration_id, request).await;
-            if let Ok(api::ResponseType_registration_confirm::Status200(response)) = response {
-                match response {
+            let error = match response {
+                Ok(api::ResponseType_registration_confirm::Status200(response)) => match response {
                     ConfirmRegistrationResponse::Ok { token: _token } => {
+                        // Confirming logs the user in, so other open tabs should follow.
+                        broadcast_auth_event(AuthEvent::LoggedIn);
                         navigator.push(&Route::DashboardHome);
                         return Ok(());
                     }
-                    ConfirmRegistrationResponse::DatabaseError => (),
-                    ConfirmRegistrationResponse::RegistrationConfirmError => (),
-                    ConfirmRegistrationResponse::UserAlreadyExists => (),
-                };
+                    ConfirmRegistrationResponse::DatabaseError => ConfirmError::DatabaseError,
+                    ConfirmRegistrationResponse::RegistrationConfirmError => {
+                        ConfirmError::RegistrationConfirmError
+                    }
+                    ConfirmRegistrationResponse::UserAlreadyExists => {
+                        ConfirmError::UserAlreadyExists
+                    }
+                },
+                Err(e) => {
+                    log::error!("Failed to confirm registration: {e}");
+                    ConfirmError::Network
+                }
             };
-            // If we fall through to here, then one of the errors occurred.
-            is_token_error.set(true);
+            confirm_error.set(Some(error));
             is_confirming.set(false);
             Ok::<(), ()>(())
         })
     };
 
+    // Submit the token from the link right away; if that fails, the filled in form is left for the user.
+    {
+        let confirm_token = confirm_token.clone();
+        let should_submit = token_from_link.is_some()
+            && matches!(
+                state_handle,
+                Ok(api::ResponseType_registration_get::Status200(_))
+            );
+        use_effect_with_deps(
+            move |_| {
+                if should_submit {
+                    confirm_token.run();
+                }
+            },
+            (),
+        );
+    }
+
     let oninput = {
         let token_info = token_info.clone();
-        let is_token_error = is_token_error.clone();
+        let confirm_error = confirm_error.clone();
         Callback::from(move |event: InputEvent| {
             let input: HtmlInputElement = event.target_unchecked_into();
             let mut info = (*token_info).clone();
             info.token = input.value();
             token_info.set(info);
-            is_token_error.set(false);
+            confirm_error.set(None);
         })
     };
 
     let onsubmit = {
+        let confirm_token = confirm_token.clone();
         Callback::from(move |event: MouseEvent| {
             event.prevent_default();
             confirm_token.run();
         })
     };
 
-    let maybe_error_alert = if *is_token_error {
-        html! {
-            <Alert style={Color::Danger}>{"Error confirming your token. Please check your email again."}</Alert>
-        }
-    } else {
-        html! {}
+    let onretry = Callback::from(move |event: MouseEvent| {
+        event.prevent_default();
+        confirm_token.run();
+    });
+
+    let maybe_error_alert = match *confirm_error {
+        None => html! {},
+        Some(ConfirmError::Network) => html! {
+            <Alert style={Color::Danger}>
+                {"We could not reach the server to confirm your registration. "}
+                <button type="button" class="btn btn-sm btn-danger" onclick={onretry}>{"Try again"}</button>
+            </Alert>
+        },
+        Some(ConfirmError::DatabaseError) => html! {
+            <Alert style={Color::Danger}>
+                {"Something went wrong on our side while confirming your registration. Your code is fine, so please try again. "}
+                <button type="button" class="btn btn-sm btn-danger" onclick={onretry}>{"Try again"}</button>
+            </Alert>
+        },
+        Some(ConfirmError::RegistrationConfirmError) => html! {
+            <Alert style={Color::Danger}>
+                {"This code is wrong or has expired. Check that it matches the latest email we sent you."}
+            </Alert>
+        },
+        Some(ConfirmError::UserAlreadyExists) => html! {
+            <Alert style={Color::Warning}>
+                {"An account with this username or email already exists, so this registration can't be completed. "}
+                {"If it's yours, you can "}<Link<Route> to={Route::Login}>{"log in"}</Link<Route>>{" instead. "}
+                {"If you've forgotten your password, ask for a sign-in link by email on the login page."}
+            </Alert>
+        },
     };
+    let is_token_error = *confirm_error == Some(ConfirmError::RegistrationConfirmError);
 
     let output = match state_handle {
-        Err(_) => html! {
-            <>
-                <h1>{"Registration not found"}</h1>
-                <p>{"We could not find a pending registration with this ID. It could have already expired."}</p>
-                <p> {"You can "}
-                <Link<Route> to={Route::Login}>{"login with an existing account"}</Link<Route>>{", or "}
-                <Link<Route> to={Route::Register}>{"try registering again"}</Link<Route>>{"."}
-                </p>
-            </>
+        Ok(api::ResponseType_registration_get::Status200(registration)) => html! {
+            <PendingRegistrationView registration={registration.clone()}>
+                <form>
+                    {maybe_error_alert}
+                    <FormTextBox id="token" input_type="text" label="Confirmation" value={token_info.token.clone()} oninput={oninput} is_invalid={is_token_error} />
+                    <FormSubmitBtn onclick={onsubmit} enabled={*is_button_enabled}>
+                        <LoadingSpinner show={*is_confirming} size={Size::Small} />
+                        { "Confirm registration" }
+                    </FormSubmitBtn>
+
+                </form>
+            </PendingRegistrationView>
         },
-        Ok(_registration) => html! {
-            // TODO: this should be refactored into a separate component, but how to pass a PendingRegistration there?
-            <form>
-                {maybe_error_alert}
-                <FormTextBox id="token" input_type="text" label="Confirmation" value={token_info.token.clone()} oninput={oninput} is_invalid={*is_token_error} />
-                <FormSubmitBtn onclick={onsubmit} enabled={*is_button_enabled}>
-                    <LoadingSpinner show={*is_confirming} size={Size::Small} />
-                    { "Confirm registration" }
-                </FormSubmitBtn>
-
-            </form>
+        Ok(api::ResponseType_registration_get::Status404(_)) | Err(_) => html! {
+            <RegistrationExpired />
         },
     };
 
     Ok(output)
 }
+
+#[function_component(RegistrationExpired)]
+fn registration_expired() -> Html {
+    html! {
+        <>
+            <h1>{"Registration not found"}</h1>
+            <p>{"We could not find a pending registration with this ID. It could have already expired."}</p>
+            <p> {"You can "}
+            <Link<Route> to={Route::Login}>{"login with an existing account"}</Link<Route>>{", or "}
+            <Link<Route> to={Route::Register}>{"try registering again"}</Link<Route>>{"."}
+            </p>
+        </>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct PendingRegistrationViewProps {
+    registration: PendingRegistration,
+    children: Children,
+}
+
+/// Shows where the confirmation code was sent, how long is left to use it, and a way to have it sent again.
+/// The children (the confirmation form) are only shown until the registration expires.
+#[function_component(PendingRegistrationView)]
+fn pending_registration_view(props: &PendingRegistrationViewProps) -> Html {
+    let update = use_update();
+    use_interval(move || update(), 1000);
+
+    let is_gone = use_state(|| false);
+    let resend_message = use_state(|| None::<(Color, &'static str)>);
+    // Unix timestamp before which the resend button is disabled.
+    let resend_available_at = use_state(|| 0.0);
+
+    let registration_id = props.registration.id;
+    let resend_fn = {
+        let is_gone = is_gone.clone();
+        let resend_message = resend_message.clone();
+        let resend_available_at = resend_available_at.clone();
+        Callback::from(move |_: ()| -> Pin<Box<dyn Future<Output = ()>>> {
+            let is_gone = is_gone.clone();
+            let resend_message = resend_message.clone();
+            let resend_available_at = resend_available_at.clone();
+            Box::pin(async move {
+                let message = match api::registration_resend_confirmation(registration_id).await {
+                    Ok(api::ResponseType_registration_resend_confirmation::Status204(_)) => (
+                        Color::Success,
+                        "We sent you a new code. It may take a minute to arrive.",
+                    ),
+                    Ok(api::ResponseType_registration_resend_confirmation::Status404(_)) => {
+                        is_gone.set(true);
+                        return;
+                    }
+                    Ok(api::ResponseType_registration_resend_confirmation::Status429(_)) => (
+                        Color::Warning,
+                        "You have asked for a new code too often. Please wait a bit before trying again.",
+                    ),
+                    Err(_) => (Color::Danger, "Could not send a new code. Please try again."),
+                };
+                resend_message.set(Some(message));
+                #[allow(unused_unsafe)]
+                let now = unsafe { get_unix_timestamp() };
+                resend_available_at.set(now + RESEND_COOLDOWN_SECONDS);
+            })
+        })
+    };
+
+    let remaining = (props.registration.expires - get_current_time()).num_seconds();
+    if *is_gone || remaining <= 0 {
+        return html! { <RegistrationExpired /> };
+    }
+
+    #[allow(unused_unsafe)]
+    let cooldown = (*resend_available_at - unsafe { get_unix_timestamp() }).ceil() as i64;
+    let resend_button_text = if cooldown > 0 {
+        format!("Resend code ({cooldown}s)")
+    } else {
+        "Resend code".to_string()
+    };
+
+    let maybe_resend_alert = match &*resend_message {
+        Some((color, message)) => html! { <Alert style={color.clone()}>{*message}</Alert> },
+        None => html! {},
+    };
+
+    html! {
+        <>
+            <p>
+                {"We sent a confirmation code to "}<strong>{props.registration.email.to_string()}</strong>{"."}
+                <br />
+                {format!("It expires in {}.", format_seconds_hms(remaining))}
+            </p>
+            { for props.children.iter() }
+            {maybe_resend_alert}
+            <p class="d-flex align-items-center">
+                <span class="me-2">{"Didn't get the email?"}</span>
+                <AsyncButton class="btn-sm" text={resend_button_text} color={crate::components::Color::Secondary} onclick_fn={resend_fn} enabled={cooldown <= 0} />
+            </p>
+        </>
+    }
+}
@@ -6,8 +6,9 @@ use yew_router::prelude::use_navigator;
 
 use crate::api;
 
-use crate::components::{DashboardLayout, ModalLoadingSpinner};
-use crate::context::UserContext;
+use crate::components::{DashboardLayout, IdleLogout, ModalLoadingSpinner, SessionExpiryWarning};
+use crate::context::{UserContext, UserContextSetter, UserSettingsContext};
+use crate::utils::auth_events::{use_auth_events, AuthEvent};
 use crate::Route;
 
 mod home;
@@ -37,23 +38,84 @@ fn dashboard_inner() -> HtmlResult {
     });
     let req_result = req_result?;
 
-    let context = use_memo(
-        |_| match &*req_result {
+    let context = use_state(|| {
+        Rc::new(match &*req_result {
             Ok(api::ResponseType_auth_get_current_token::Status200(info)) => {
                 UserContext::LoggedIn(info.clone())
             }
             Err(_) => UserContext::LoggedOut,
-        },
-        (),
-    );
+        })
+    });
+    let context_setter = {
+        let context = context.clone();
+        UserContextSetter(Callback::from(move |new_context: UserContext| {
+            context.set(Rc::new(new_context));
+        }))
+    };
+
+    // The settings are not needed to show the dashboard, so they load in the background.
+    let user_settings = use_state(|| None);
+    {
+        let user_settings = user_settings.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match api::user_get_settings().await {
+                        Ok(api::ResponseType_user_get_settings::Status200(settings)) => {
+                            user_settings.set(Some(settings));
+                        }
+                        Err(e) => log::error!("Failed to load user settings: {e}"),
+                    }
+                });
+            },
+            (),
+        );
+    }
+    let settings_context = {
+        let user_settings_out = user_settings.clone();
+        UserSettingsContext {
+            settings: (*user_settings).clone(),
+            set: Callback::from(move |settings| user_settings_out.set(Some(settings))),
+        }
+    };
+
+    // Follow logins and logouts that happen in other tabs.
+    {
+        let context = context.clone();
+        let navigator = navigator.clone();
+        use_auth_events(Callback::from(move |event| match event {
+            AuthEvent::LoggedOut => {
+                // Leaving the dashboard drops its UserContext; setting it to LoggedOut first
+                // would make the tabs that expect a logged in user panic on the way out.
+                navigator.push(&Route::Login);
+            }
+            AuthEvent::LoggedIn => {
+                // This may be a different user, so reload who we are.
+                let context = context.clone();
+                let navigator = navigator.clone();
+                spawn_local(async move {
+                    match api::auth_get_current_token().await {
+                        Ok(api::ResponseType_auth_get_current_token::Status200(info)) => {
+                            context.set(Rc::new(UserContext::LoggedIn(info)));
+                        }
+                        Err(_) => navigator.push(&Route::Login),
+                    }
+                });
+            }
+        }));
+    }
 
     let result_html = match &*req_result {
         Ok(_) => {
             log::info!("Dashboard: Logged in with {:?}", context);
 
             html! {
-                <ContextProvider<Rc<UserContext>> context={context}>
+                <ContextProvider<Rc<UserContext>> context={(*context).clone()}>
+                <ContextProvider<UserContextSetter> context={context_setter}>
+                <ContextProvider<UserSettingsContext> context={settings_context}>
                 <DashboardLayout>
+                    <SessionExpiryWarning />
+                    <IdleLogout />
                     <div class="row">
                         <div class="col-2">
                             <div class="nav flex-column nav-pills" id="v-pills-tab" role="tablist" aria-orientation="vertical">
@@ -68,6 +130,8 @@ fn dashboard_inner() -> HtmlResult {
                     </div>
 
                 </DashboardLayout>
+                </ContextProvider<UserSettingsContext>>
+                </ContextProvider<UserContextSetter>>
                 </ContextProvider<Rc<UserContext>>>
             }
         }
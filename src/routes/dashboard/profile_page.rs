@@ -1,6 +1,9 @@
 use yew::prelude::*;
 
-use crate::components::SessionList;
+use crate::components::{
+    IdleLogoutSettings, InviteManager, PasskeyList, RecoveryCodeManager, SecurityActivity,
+    SessionExpiryWarningSettings, SessionList,
+};
 
 #[function_component(DashboardProfile)]
 pub fn dashboard_profile() -> Html {
@@ -14,6 +17,36 @@ pub fn dashboard_profile() -> Html {
                     <SessionList />
                 </div>
             </div>
+            <div class="row">
+                <div class="col">
+                    <SecurityActivity />
+                </div>
+            </div>
+            <div class="row">
+                <div class="col">
+                    <PasskeyList />
+                </div>
+            </div>
+            <div class="row">
+                <div class="col">
+                    <RecoveryCodeManager />
+                </div>
+            </div>
+            <div class="row">
+                <div class="col">
+                    <IdleLogoutSettings />
+                </div>
+            </div>
+            <div class="row">
+                <div class="col">
+                    <SessionExpiryWarningSettings />
+                </div>
+            </div>
+            <div class="row">
+                <div class="col">
+                    <InviteManager />
+                </div>
+            </div>
         </>
     }
 }
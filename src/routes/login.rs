@@ -1,21 +1,30 @@
 use api_types::v1::LoginSuccess;
+use api_types::v1::MagicLinkRequest;
+use api_types::v1::PasskeyAssertion;
+use api_types::Snowflake;
 use serde::Deserialize;
 use serde::Serialize;
 use yew::prelude::*;
 
+use yew_bootstrap::component::Alert;
 use yew_hooks::use_async;
 use yew_router::prelude::*;
 
 use crate::api::*;
 
 use crate::components::CenteredBox;
+use crate::components::Color;
 use crate::components::FormSubmitBtn;
 use crate::components::FormTextBox;
 use crate::components::Size;
+use crate::utils::auth_events::{broadcast_auth_event, use_auth_events, AuthEvent};
+use crate::utils::webauthn::{are_passkeys_supported, get_passkey};
 use crate::Route;
 use web_sys::HtmlInputElement;
 
 use crate::components::LoadingSpinner;
+use crate::components::OidcProviderButtons;
+use crate::components::SecondFactorForm;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct LoginInfo {
@@ -37,16 +46,87 @@ pub struct LoginInfoWrapper {
     pub user: LoginInfo,
 }
 
+/// Runs the whole passkey sign-in ceremony:
+/// fetch a challenge, let the browser sign it with a passkey, and send the assertion back.
+async fn sign_in_with_passkey() -> Result<(), String> {
+    let challenge = match auth_passkey_login_start().await {
+        Ok(ResponseType_auth_passkey_login_start::Status200(challenge)) => challenge,
+        Err(e) => return Err(format!("Could not reach the server: {e}")),
+    };
+    let credential = get_passkey(challenge.options).await?;
+    match auth_passkey_login_finish(challenge.id, PasskeyAssertion { credential }).await {
+        Ok(ResponseType_auth_passkey_login_finish::Status200(_)) => Ok(()),
+        Ok(_) => Err("This passkey is not registered to any account.".to_string()),
+        Err(e) => Err(format!("Could not reach the server: {e}")),
+    }
+}
+
 #[function_component(Login)]
 pub fn login_page() -> Html {
     // Try logging in.
     let login_info = use_state(LoginInfo::default);
     let navigator = use_navigator().unwrap();
     let is_logging_in = use_state(|| false);
+    let passkey_error = use_state(|| None::<String>);
+    // Set once the password was accepted, if the account also needs a second factor.
+    let second_factor_challenge = use_state(|| None::<Snowflake>);
+
+    // `None` until a sign-in link has been requested, then whether that worked.
+    let magic_link_result = use_state(|| None::<Result<(), String>>);
+    let request_magic_link = {
+        let login_info = login_info.clone();
+        let magic_link_result = magic_link_result.clone();
+        use_async(async move {
+            let request = MagicLinkRequest {
+                email: login_info.login.clone(),
+            };
+            let result = match auth_request_magic_link(request).await {
+                Ok(ResponseType_auth_request_magic_link::Status204(_)) => Ok(()),
+                Err(e) => Err(format!("Could not reach the server: {e}")),
+            };
+            magic_link_result.set(Some(result));
+            Ok::<(), ()>(())
+        })
+    };
+
+    // If the user logs in from another tab, follow them to the dashboard.
+    {
+        let navigator = navigator.clone();
+        use_auth_events(Callback::from(move |event| {
+            if event == AuthEvent::LoggedIn {
+                navigator.push(&Route::DashboardHome);
+            }
+        }));
+    }
+
+    let passkey_login = {
+        let navigator = navigator.clone();
+        let is_logging_in = is_logging_in.clone();
+        let passkey_error = passkey_error.clone();
+        use_async(async move {
+            is_logging_in.set(true);
+            passkey_error.set(None);
+            match sign_in_with_passkey().await {
+                Ok(()) => {
+                    log::info!("Logged in with passkey!");
+                    broadcast_auth_event(AuthEvent::LoggedIn);
+                    navigator.push(&Route::DashboardHome);
+                }
+                Err(e) => {
+                    log::info!("Failed to log in with passkey: {e}");
+                    passkey_error.set(Some(e));
+                    is_logging_in.set(false);
+                }
+            }
+            Ok::<(), ()>(())
+        })
+    };
 
     let user_login = {
+        let navigator = navigator.clone();
         let login_info = login_info.clone();
         let is_logging_in = is_logging_in.clone();
+        let second_factor_challenge = second_factor_challenge.clone();
         use_async(async move {
             let request = (*login_info).clone().into();
             is_logging_in.set(true);
@@ -54,9 +134,15 @@ pub fn login_page() -> Html {
             match response {
                 Ok(ResponseType_auth_login::Status200(LoginSuccess { token: _ })) => {
                     log::info!("Logged in!");
+                    broadcast_auth_event(AuthEvent::LoggedIn);
 
                     navigator.push(&Route::DashboardHome);
                 }
+                Ok(ResponseType_auth_login::Status202(challenge)) => {
+                    log::info!("Password accepted, asking for the second factor");
+                    is_logging_in.set(false);
+                    second_factor_challenge.set(Some(challenge.id));
+                }
                 _ => {
                     log::info!("Failed to log in!");
                     is_logging_in.set(false);
@@ -93,6 +179,72 @@ pub fn login_page() -> Html {
             user_login.run();
         })
     };
+
+    let passkey_html = if are_passkeys_supported() {
+        let onclick = Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            passkey_login.run();
+        });
+        let maybe_error_alert = match &*passkey_error {
+            Some(e) => html! {
+                <Alert style={yew_bootstrap::util::Color::Danger}>{"Could not sign in with a passkey. "}{e}</Alert>
+            },
+            None => html! {},
+        };
+        html! {
+            <>
+                {maybe_error_alert}
+                <FormSubmitBtn color={Color::Secondary} onclick={onclick} enabled={!*is_logging_in}>
+                    { "Sign in with a passkey" }
+                </FormSubmitBtn>
+            </>
+        }
+    } else {
+        html! {}
+    };
+
+    let magic_link_html = {
+        let maybe_alert = match &*magic_link_result {
+            Some(Ok(())) => html! {
+                <Alert style={yew_bootstrap::util::Color::Success}>
+                    {format!("If there is an account for {}, a sign-in link is on its way there. It can only be used once.", login_info.login)}
+                </Alert>
+            },
+            Some(Err(e)) => html! {
+                <Alert style={yew_bootstrap::util::Color::Danger}>{"Could not send a sign-in link. "}{e}</Alert>
+            },
+            None => html! {},
+        };
+        let is_requesting = request_magic_link.loading;
+        let onclick = Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            request_magic_link.run();
+        });
+        html! {
+            <>
+                {maybe_alert}
+                <button type="button" class="w-100 mb-2 btn btn-link" disabled={is_requesting || login_info.login.is_empty()} {onclick}>
+                    <LoadingSpinner show={is_requesting} size={Size::Small} />
+                    { "Email me a sign-in link instead" }
+                </button>
+            </>
+        }
+    };
+
+    if let Some(challenge_id) = *second_factor_challenge {
+        let on_cancel = Callback::from(move |_| second_factor_challenge.set(None));
+        let on_success = Callback::from(move |_| {
+            log::info!("Logged in!");
+            broadcast_auth_event(AuthEvent::LoggedIn);
+            navigator.push(&Route::DashboardHome);
+        });
+        return html! {
+            <CenteredBox title={"Login"} >
+                <SecondFactorForm {challenge_id} {on_cancel} {on_success} />
+            </CenteredBox>
+        };
+    }
+
     html! {
         <CenteredBox title={"Login"} >
             <form>
@@ -102,7 +254,10 @@ pub fn login_page() -> Html {
                     <LoadingSpinner show={*is_logging_in} size={Size::Small} />
                     { "Login" }
                 </FormSubmitBtn>
+                {passkey_html}
+                {magic_link_html}
             </form>
+            <OidcProviderButtons />
         <p> {"Or "} <Link<Route> to={Route::Register}>{"create an account"}</Link<Route>>{" instead?"}</p>
         </CenteredBox>
     }
@@ -0,0 +1,63 @@
+use api_types::v1::MagicLinkConfirmRequest;
+use api_types::Snowflake;
+use yew::prelude::*;
+use yew_bootstrap::component::Alert;
+use yew_bootstrap::util::Color;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::api::*;
+use crate::components::{CenteredBox, ModalLoadingSpinner};
+use crate::utils::auth_events::{broadcast_auth_event, AuthEvent};
+use crate::Route;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct MagicLinkLoginProps {
+    pub link_id: Snowflake,
+    pub token: AttrValue,
+}
+
+/// Landing page for the sign-in links sent by email.
+/// Exchanges the link's token for a session as soon as it is opened.
+#[function_component(MagicLinkLogin)]
+pub fn magic_link_login(props: &MagicLinkLoginProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let link_id = props.link_id;
+    let token = props.token.to_string();
+
+    let consume_link = use_async_with_options(
+        async move {
+            let request = MagicLinkConfirmRequest { token };
+            match auth_consume_magic_link(link_id, request).await {
+                Ok(ResponseType_auth_consume_magic_link::Status200(_)) => {
+                    log::info!("Logged in with a sign-in link!");
+                    broadcast_auth_event(AuthEvent::LoggedIn);
+                    navigator.replace(&Route::DashboardHome);
+                    Ok(())
+                }
+                Ok(_) => Err("This sign-in link is invalid, has expired, or has already been used."),
+                Err(e) => {
+                    log::error!("Failed to use sign-in link: {e}");
+                    Err("Could not reach the server. Try opening the link again.")
+                }
+            }
+        },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    match consume_link.error {
+        Some(e) => html! {
+            <CenteredBox title={"Login"}>
+                <Alert style={Color::Danger}>{e}</Alert>
+                <p>
+                    {"You can "}
+                    <Link<Route> to={Route::Login}>{"go back to the login page"}</Link<Route>>
+                    {" and request a new link."}
+                </p>
+            </CenteredBox>
+        },
+        None => html! {
+            <ModalLoadingSpinner text="Signing you in..." />
+        },
+    }
+}
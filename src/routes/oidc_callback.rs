@@ -0,0 +1,166 @@
+use api_types::v1::OidcCallbackRequest;
+use serde::Deserialize;
+#[cfg(debug_assertions)]
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_bootstrap::component::Alert;
+use yew_bootstrap::util::Color;
+use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use yew_router::prelude::*;
+
+use crate::api::*;
+use crate::components::{CenteredBox, ModalLoadingSpinner};
+#[cfg(debug_assertions)]
+use crate::components::{FormSubmitBtn, FormTextBox};
+use crate::utils::auth_events::{broadcast_auth_event, AuthEvent};
+#[cfg(debug_assertions)]
+use crate::utils::encode_uri_component;
+use crate::utils::oidc::{redirect_uri, take_pending_login};
+use crate::Route;
+
+/// The query parameters that an OpenID Connect provider redirects back with.
+#[derive(Deserialize, Clone, Debug, Default)]
+struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Exchange the authorization code from the provider for a session, like a successful `auth_login`.
+async fn exchange_code(query: OidcCallbackQuery) -> Result<(), String> {
+    if let Some(error) = query.error {
+        return Err(query.error_description.unwrap_or(error));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| "The provider did not send an authorization code.".to_string())?;
+    let pending = take_pending_login().ok_or_else(|| {
+        "This sign-in attempt has expired or was started in a different tab.".to_string()
+    })?;
+    if query.state.as_deref() != Some(pending.state.as_str()) {
+        return Err("The sign-in response did not match the request that was sent.".to_string());
+    }
+
+    let request = OidcCallbackRequest {
+        provider: pending.provider,
+        code,
+        code_verifier: pending.code_verifier,
+        nonce: pending.nonce,
+        redirect_uri: redirect_uri(),
+    };
+    match auth_oidc_callback(request).await {
+        Ok(ResponseType_auth_oidc_callback::Status200(_)) => Ok(()),
+        Ok(ResponseType_auth_oidc_callback::Status401(_)) => {
+            Err("There is no account linked to this identity.".to_string())
+        }
+        Err(e) => Err(format!("Could not reach the server: {e}")),
+    }
+}
+
+#[function_component(OidcCallback)]
+pub fn oidc_callback() -> Html {
+    let navigator = use_navigator().unwrap();
+    let location = use_location().unwrap();
+    let query = location.query::<OidcCallbackQuery>().unwrap_or_default();
+
+    let exchange = use_async_with_options(
+        async move {
+            match exchange_code(query).await {
+                Ok(()) => {
+                    log::info!("Logged in through OpenID Connect!");
+                    broadcast_auth_event(AuthEvent::LoggedIn);
+                    navigator.replace(&Route::DashboardHome);
+                    Ok(())
+                }
+                Err(e) => {
+                    log::info!("Failed to log in through OpenID Connect: {e}");
+                    Err(e)
+                }
+            }
+        },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    match &exchange.error {
+        Some(e) => html! {
+            <CenteredBox title={"Login"}>
+                <Alert style={Color::Danger}>{"Could not sign you in. "}{e}</Alert>
+                <p><Link<Route> to={Route::Login}>{"Back to login"}</Link<Route>></p>
+            </CenteredBox>
+        },
+        None => html! {
+            <ModalLoadingSpinner text="Signing you in..." />
+        },
+    }
+}
+
+/// The query parameters that the mock provider page is opened with.
+#[cfg(debug_assertions)]
+#[derive(Deserialize, Clone, Debug, Default)]
+struct MockProviderQuery {
+    redirect_uri: String,
+    state: String,
+}
+
+/// A stand-in authorization page for local development and tests.
+///
+/// The API's mock provider points here; it accepts any name and
+/// redirects back with a `mock:<name>` code that the mock provider exchanges for that user.
+/// Only compiled into debug builds.
+#[cfg(debug_assertions)]
+#[function_component(MockOidcProvider)]
+pub fn mock_oidc_provider() -> Html {
+    let location = use_location().unwrap();
+    let name = use_state(String::new);
+
+    let query = match location.query::<MockProviderQuery>() {
+        Ok(query) => query,
+        _ => return html! { <h1>{ "404" }</h1> },
+    };
+    // Only ever send the browser back to this app, never to an arbitrary (or `javascript:`) URL.
+    if query.redirect_uri != redirect_uri() {
+        return html! {
+            <CenteredBox title={"Mock identity provider"}>
+                <Alert style={Color::Danger}>{"The redirect URI does not belong to this app."}</Alert>
+            </CenteredBox>
+        };
+    }
+
+    let oninput = {
+        let name = name.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+
+    let onsubmit = {
+        let name = name.clone();
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            #[allow(unused_unsafe)]
+            let url = unsafe {
+                format!(
+                    "{}?code={}&state={}",
+                    redirect_uri(),
+                    encode_uri_component(&format!("mock:{}", *name)),
+                    encode_uri_component(&query.state),
+                )
+            };
+            if let Some(window) = web_sys::window() {
+                window.location().set_href(&url).ok();
+            }
+        })
+    };
+
+    html! {
+        <CenteredBox title={"Mock identity provider"}>
+            <Alert style={Color::Warning}>{"This page only exists in development builds."}</Alert>
+            <form>
+                <FormTextBox id="mock_name" label="Sign in as" value={(*name).clone()} {oninput} />
+                <FormSubmitBtn onclick={onsubmit}>{ "Continue" }</FormSubmitBtn>
+            </form>
+        </CenteredBox>
+    }
+}
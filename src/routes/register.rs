@@ -1,14 +1,16 @@
+use std::{future::Future, pin::Pin, rc::Rc, time::Duration};
+
+use api_types::v1::{AvailabilityCheck, AvailabilityResponse, PasswordPolicy, RegistrationResponse};
 use serde::Deserialize;
 use serde::Serialize;
-use web_sys::HtmlElement;
-use web_sys::HtmlInputElement;
-use web_sys::Node;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::platform::spawn_local;
+use yew::platform::time::sleep;
 use yew::prelude::*;
 use yew::suspense::use_future;
 use yew_bootstrap::component::Alert;
 use yew_bootstrap::util::Color;
 use yew_hooks::use_async;
-use yew_hooks::use_is_first_mount;
 use yew_router::prelude::*;
 
 use crate::api;
@@ -16,24 +18,94 @@ use crate::api;
 use crate::Route;
 
 use crate::api::registration_get_prerequisites;
+use crate::components::AsyncTextBox;
+use crate::components::AsyncTextBoxValidationResult;
+use crate::components::CaptchaWidget;
 use crate::components::CenteredBox;
 use crate::components::FormSubmitBtn;
 use crate::components::FormTextBox;
 use crate::components::LoadingSpinner;
+use crate::components::OidcProviderButtons;
+use crate::components::PasswordStrengthMeter;
 use crate::components::Size;
-
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+use crate::utils::captcha;
+use crate::utils::password_strength::unmet_requirements;
+use crate::utils::time::browser_timezone;
+
+/// Everything entered into the registration form.
+///
+/// This is kept in `sessionStorage` as a draft, without the passwords and the captcha.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct RegisterInfo {
     pub username: String,
     pub email: String,
+    /// Only needed if the server requires an invite.
+    pub invite_code: String,
+    #[serde(skip)]
     pub password: String,
-    pub hcaptcha_response: String,
+    #[serde(skip)]
+    pub password_confirm: String,
+    /// The version of the terms of service and privacy policy that the user accepted.
+    pub accepted_terms_version: Option<String>,
+    pub timezone: String,
+    pub sleep_goal_minutes: Option<u32>,
+    #[serde(skip)]
+    pub captcha_response: String,
 }
 
 pub enum RegisterError {
     EmailNotValid,
+    TermsNotAccepted,
 }
 
+/// Why submitting the registration failed, as shown above the form.
+#[derive(Clone, Debug, PartialEq)]
+enum RegistrationFailure {
+    /// The server could not be reached.
+    Network,
+    /// The server could not save the registration.
+    DatabaseError,
+    /// The captcha was not solved, or the server rejected the solution.
+    Captcha(String),
+    EmailNotValid,
+    TermsNotAccepted,
+    /// The invite code is missing, unknown, used up, revoked or expired.
+    InvalidInvite,
+}
+
+impl RegistrationFailure {
+    fn message(&self) -> String {
+        match self {
+            Self::Network => {
+                "We could not reach the server. Check your connection and try again.".to_string()
+            }
+            Self::DatabaseError => {
+                "Something went wrong on our side while registering. Please try again in a moment."
+                    .to_string()
+            }
+            Self::Captcha(error) if error.is_empty() => {
+                "Please complete the captcha before registering.".to_string()
+            }
+            Self::Captcha(error) => format!("The captcha could not be verified ({error}). Please solve it again."),
+            Self::EmailNotValid => "Enter a valid email address.".to_string(),
+            Self::TermsNotAccepted => {
+                "Accept the terms of service and privacy policy to register.".to_string()
+            }
+            Self::InvalidInvite => {
+                "This invite code is not valid. It may have been used already, revoked, or expired."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// How many times to send the registration before giving up on transient failures,
+/// when no captcha is needed.
+const REGISTRATION_ATTEMPTS: u32 = 3;
+
+/// How long to wait before the first retry. Each later retry waits twice as long.
+const REGISTRATION_RETRY_DELAY: Duration = Duration::from_millis(1000);
+
 impl TryFrom<RegisterInfo> for api_types::v1::RegistrationRequest {
     type Error = RegisterError;
 
@@ -45,7 +117,13 @@ impl TryFrom<RegisterInfo> for api_types::v1::RegistrationRequest {
                 .parse()
                 .map_err(|_| RegisterError::EmailNotValid)?,
             password: val.password,
-            hcaptcha_response: val.hcaptcha_response,
+            invite_code: Some(val.invite_code).filter(|code| !code.is_empty()),
+            accepted_terms_version: val
+                .accepted_terms_version
+                .ok_or(RegisterError::TermsNotAccepted)?,
+            timezone: Some(val.timezone).filter(|timezone| !timezone.is_empty()),
+            sleep_goal_minutes: val.sleep_goal_minutes,
+            captcha_response: val.captcha_response,
         })
     }
 }
@@ -54,16 +132,154 @@ fn validate_user(info: &RegisterInfo) -> bool {
     !info.username.is_empty()
 }
 
-fn validate_password(info: &RegisterInfo) -> bool {
-    !info.password.is_empty()
+fn validate_password(info: &RegisterInfo, policy: &PasswordPolicy) -> bool {
+    !info.password.is_empty() && unmet_requirements(&info.password, policy).is_empty()
+}
+
+fn validate_password_confirm(info: &RegisterInfo) -> bool {
+    info.password == info.password_confirm
 }
 
 fn validate_email(info: &RegisterInfo) -> bool {
     let maybe_reg_req: Result<api_types::v1::RegistrationRequest, _> = info.clone().try_into();
-    maybe_reg_req.is_ok()
+    !matches!(maybe_reg_req, Err(RegisterError::EmailNotValid))
+}
+
+/// Ask the API whether a username or email is still free.
+async fn check_availability(
+    check: AvailabilityCheck,
+    taken_message: &str,
+) -> AsyncTextBoxValidationResult {
+    match api::registration_check_availability(check).await {
+        Ok(api::ResponseType_registration_check_availability::Status200(AvailabilityResponse {
+            available: true,
+        })) => AsyncTextBoxValidationResult::Ok,
+        Ok(api::ResponseType_registration_check_availability::Status200(_)) => {
+            AsyncTextBoxValidationResult::Invalid(taken_message.to_string())
+        }
+        Err(e) => {
+            // The server checks again when registering, so don't block the user on this.
+            log::error!("Failed to check availability: {e}");
+            AsyncTextBoxValidationResult::Ok
+        }
+    }
+}
+
+fn check_username(
+    username: String,
+) -> Pin<Box<dyn Future<Output = AsyncTextBoxValidationResult>>> {
+    Box::pin(async move {
+        if username.is_empty() {
+            return AsyncTextBoxValidationResult::Invalid("Choose a username.".to_string());
+        }
+        check_availability(
+            AvailabilityCheck::Username(username),
+            "This username is already taken.",
+        )
+        .await
+    })
+}
+
+fn check_email(
+    email: String,
+) -> Pin<Box<dyn Future<Output = AsyncTextBoxValidationResult>>> {
+    Box::pin(async move {
+        let info = RegisterInfo {
+            email: email.clone(),
+            ..Default::default()
+        };
+        if !validate_email(&info) {
+            return AsyncTextBoxValidationResult::Invalid(
+                "Enter a valid email address.".to_string(),
+            );
+        }
+        check_availability(
+            AvailabilityCheck::Email(email),
+            "There is already an account with this email. Try logging in instead.",
+        )
+        .await
+    })
+}
+
+/// How long to wait after the last keystroke before checking availability, in milliseconds.
+const AVAILABILITY_CHECK_DEBOUNCE_MS: u32 = 500;
+
+/// Sleep goals offered on the preferences step, in minutes.
+const SLEEP_GOAL_CHOICES: [u32; 9] = [360, 390, 420, 450, 480, 510, 540, 570, 600];
+
+/// Key in `sessionStorage` under which the unfinished registration is kept,
+/// so that it survives the user leaving the page and coming back.
+const DRAFT_KEY: &str = "registration_draft";
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+fn load_draft() -> Option<RegisterInfo> {
+    let draft = session_storage()?.get_item(DRAFT_KEY).ok()??;
+    serde_json::from_str(&draft).ok()
+}
+
+fn save_draft(info: &RegisterInfo) {
+    if let (Some(storage), Ok(draft)) = (session_storage(), serde_json::to_string(info)) {
+        storage.set_item(DRAFT_KEY, &draft).ok();
+    }
+}
+
+fn clear_draft() {
+    if let Some(storage) = session_storage() {
+        storage.remove_item(DRAFT_KEY).ok();
+    }
+}
+
+/// The pages of the registration form, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RegisterStep {
+    Account,
+    Terms,
+    Preferences,
+    Captcha,
+}
+
+impl RegisterStep {
+    const ALL: [RegisterStep; 4] = [
+        Self::Account,
+        Self::Terms,
+        Self::Preferences,
+        Self::Captcha,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Account => "Account",
+            Self::Terms => "Terms",
+            Self::Preferences => "Preferences",
+            Self::Captcha => "Verify",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|step| step == self).unwrap()
+    }
+
+    fn next(&self) -> Option<Self> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+
+    fn previous(&self) -> Option<Self> {
+        self.index().checked_sub(1).map(|index| Self::ALL[index])
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RegisterProps {
+    /// Invite code to fill in, from an invite link.
+    #[prop_or_default]
+    pub invite_code: Option<AttrValue>,
 }
+
 #[function_component(Register)]
-pub fn register() -> Html {
+pub fn register(props: &RegisterProps) -> Html {
     html! {
         <Suspense fallback={html!(
             <CenteredBox title={"Registration"} >
@@ -73,23 +289,13 @@ pub fn register() -> Html {
                 </Alert>
             </CenteredBox>
         )}>
-            <RegisterInner />
+            <RegisterInner invite_code={props.invite_code.clone()} />
         </Suspense>
     }
 }
 
-#[wasm_bindgen::prelude::wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace=hcaptcha, js_name=getResponse)]
-    fn get_hcaptcha_response(widget_id: String) -> String;
-
-    #[wasm_bindgen(js_namespace=hcaptcha, js_name=render)]
-    fn render_hcaptcha(container: HtmlElement) -> String;
-
-}
-
 #[function_component(RegisterInner)]
-pub fn register_inner() -> HtmlResult {
+pub fn register_inner(props: &RegisterProps) -> HtmlResult {
     let registration_prereqs = use_future(|| async { registration_get_prerequisites().await })?;
 
     let prereqs = match &*registration_prereqs {
@@ -107,136 +313,280 @@ pub fn register_inner() -> HtmlResult {
         }
     };
 
-    let register_info = use_state(RegisterInfo::default);
+    let terms = prereqs.terms.clone();
+    let restored_draft = use_memo(|_| load_draft(), ());
+    let register_info = {
+        let terms_version = terms.version.clone();
+        let restored_draft = restored_draft.clone();
+        let invite_code = props.invite_code.clone();
+        use_state(move || {
+            let mut info = (*restored_draft).clone().unwrap_or_default();
+            if let Some(invite_code) = invite_code {
+                info.invite_code = invite_code.to_string();
+            }
+            // Accepting an older version of the terms doesn't count.
+            if info.accepted_terms_version.as_ref() != Some(&terms_version) {
+                info.accepted_terms_version = None;
+            }
+            if info.timezone.is_empty() {
+                info.timezone = browser_timezone();
+            }
+            info
+        })
+    };
+    // The passwords are not part of the draft, so every visit starts with the account details.
+    let step = use_state(|| RegisterStep::Account);
     let is_registering = use_state(|| false);
-    let is_username_error = use_state(|| true);
-    let is_email_error = use_state(|| true);
+    let registration_failure = use_state(|| None::<RegistrationFailure>);
+    // What was actually submitted, since the state above only updates on the next render.
+    let submitted_info = use_mut_ref(RegisterInfo::default);
+    let captcha_config = prereqs.captcha.clone();
+    // Only set once the captcha has been shown, which can fail if its script is blocked.
+    let widget_id = use_state(|| None::<String>);
+    let is_username_available = use_state(|| false);
+    let is_email_available = use_state(|| false);
     let is_password_error = use_state(|| true);
+    let is_password_confirm_error = use_state(|| false);
     let is_button_enabled = use_state(|| false);
     let navigator = use_navigator().unwrap();
+    let password_policy = prereqs.password_policy.clone();
+
+    use_effect_with_deps(save_draft, (*register_info).clone());
+
+    // The text boxes only check availability while typing, so check the restored values once here.
+    {
+        let is_username_available = is_username_available.clone();
+        let is_email_available = is_email_available.clone();
+        let restored_draft = restored_draft.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(info) = (*restored_draft).clone() {
+                    spawn_local(async move {
+                        if !info.username.is_empty() {
+                            is_username_available.set(
+                                check_username(info.username).await
+                                    == AsyncTextBoxValidationResult::Ok,
+                            );
+                        }
+                        if !info.email.is_empty() {
+                            is_email_available.set(
+                                check_email(info.email).await == AsyncTextBoxValidationResult::Ok,
+                            );
+                        }
+                    });
+                }
+            },
+            (),
+        );
+    }
 
-    let validate_info = |info: &RegisterInfo| {
-        validate_email(info) && validate_password(info) && validate_user(info)
+    // Shared by all of the inputs: check every field, and store the new info.
+    let revalidate = {
+        let register_info = register_info.clone();
+        let is_button_enabled = is_button_enabled.clone();
+        let is_password_error = is_password_error.clone();
+        let is_password_confirm_error = is_password_confirm_error.clone();
+        let password_policy = password_policy.clone();
+        Rc::new(move |info: RegisterInfo| {
+            let is_password_valid = validate_password(&info, &password_policy);
+            let is_password_confirm_valid = validate_password_confirm(&info);
+            is_password_error.set(!is_password_valid);
+            // Don't complain about the confirmation until the user has started typing it.
+            is_password_confirm_error
+                .set(!info.password_confirm.is_empty() && !is_password_confirm_valid);
+            is_button_enabled.set(
+                validate_email(&info)
+                    && is_password_valid
+                    && is_password_confirm_valid
+                    && validate_user(&info),
+            );
+            register_info.set(info);
+        })
+    };
+
+    let go_to_step = {
+        let step = step.clone();
+        let widget_id = widget_id.clone();
+        let registration_failure = registration_failure.clone();
+        Callback::from(move |new_step: RegisterStep| {
+            // The captcha widget is created again when its step is shown.
+            widget_id.set(None);
+            registration_failure.set(None);
+            step.set(new_step);
+        })
     };
 
     let perform_registration = {
-        let register_info = register_info.clone();
+        let submitted_info = submitted_info.clone();
         let is_registering = is_registering.clone();
-        let is_button_enabled = is_button_enabled.clone();
-        let is_email_error = is_email_error.clone();
+        let registration_failure = registration_failure.clone();
+        let is_email_available = is_email_available.clone();
+        let widget_id = widget_id.clone();
+        let step = step.clone();
+        let captcha_config = captcha_config.clone();
         use_async(async move {
-            let request = (*register_info).clone();
+            let info = submitted_info.borrow().clone();
             is_registering.set(true);
-            is_button_enabled.set(true);
-            let maybe_request = request.try_into();
-            match maybe_request {
-                Ok(request) => {
-                    let response = api::auth_register(request).await;
-                    is_registering.set(false);
-                    match response {
-                        Ok(api::ResponseType_auth_register::Status200(resp)) => match resp {
-                            api_types::v1::RegistrationResponse::Ok { id } => {
-                                navigator.push(&Route::ConfirmRegister { id });
-                            }
-                            api_types::v1::RegistrationResponse::PendingRegistrationExists {
-                                id,
-                            } => {
+            registration_failure.set(None);
+
+            let is_captcha_missing =
+                captcha::is_required(&captcha_config) && info.captcha_response.is_empty();
+            let failure = if is_captcha_missing {
+                RegistrationFailure::Captcha(String::new())
+            } else {
+                match info.try_into() {
+                    Ok(request) => {
+                        // A captcha solution is used up once the server has checked it, even if saving
+                        // the registration fails afterwards, so it can't be sent again.
+                        let attempts = match captcha::is_required(&captcha_config) {
+                            true => 1,
+                            false => REGISTRATION_ATTEMPTS,
+                        };
+                        match register_with_retries(request, attempts).await {
+                            Ok(id) => {
+                                is_registering.set(false);
+                                clear_draft();
                                 navigator.push(&Route::ConfirmRegister { id });
+                                return Ok(());
                             }
-                            api_types::v1::RegistrationResponse::DatabaseError => {
-                                // TODO: retry?
-                            }
-                            api_types::v1::RegistrationResponse::HcaptchaFailure { error } => {
-                                log::error!("Hcaptcha Error received: {error}");
-                            }
-                        },
-                        Err(_) => {
-                            // TODO: retry?
+                            Err(failure) => failure,
                         }
                     }
-                }
-                Err(err) => match err {
-                    RegisterError::EmailNotValid => {
-                        // Highlight the email input
-                        is_email_error.set(true);
+                    Err(RegisterError::EmailNotValid) => {
+                        // Keep the form from being submitted until the email is fixed
+                        is_email_available.set(false);
+                        RegistrationFailure::EmailNotValid
                     }
-                },
+                    Err(RegisterError::TermsNotAccepted) => RegistrationFailure::TermsNotAccepted,
+                }
             };
 
+            match failure {
+                // A captcha solution can only be used once, so the user has to solve a new one.
+                // The server may have checked it before it failed, so that applies to transient failures too.
+                RegistrationFailure::Captcha(_)
+                | RegistrationFailure::Network
+                | RegistrationFailure::DatabaseError => {
+                    if let Some(widget_id) = &*widget_id {
+                        captcha::reset(&captcha_config, widget_id);
+                    }
+                }
+                RegistrationFailure::EmailNotValid | RegistrationFailure::InvalidInvite => {
+                    widget_id.set(None);
+                    step.set(RegisterStep::Account);
+                }
+                RegistrationFailure::TermsNotAccepted => {
+                    widget_id.set(None);
+                    step.set(RegisterStep::Terms);
+                }
+            }
+            registration_failure.set(Some(failure));
+            is_registering.set(false);
             Ok::<(), ()>(())
         })
     };
 
     let oninput_username = {
         let register_info = register_info.clone();
-        let is_button_enabled = is_button_enabled.clone();
-        let is_username_error = is_username_error.clone();
-        let is_password_error = is_password_error.clone();
-        let is_email_error = is_email_error.clone();
+        let revalidate = revalidate.clone();
+        let is_username_available = is_username_available.clone();
+        Callback::from(move |username: String| {
+            let mut info = (*register_info).clone();
+            info.username = username;
+            is_username_available.set(false);
+            revalidate(info);
+        })
+    };
 
+    let onvalidated_username = {
+        let is_username_available = is_username_available.clone();
+        Callback::from(move |result: AsyncTextBoxValidationResult| {
+            is_username_available.set(result == AsyncTextBoxValidationResult::Ok);
+        })
+    };
+
+    let oninput_email = {
+        let register_info = register_info.clone();
+        let revalidate = revalidate.clone();
+        let is_email_available = is_email_available.clone();
+        Callback::from(move |email: String| {
+            let mut info = (*register_info).clone();
+            info.email = email;
+            is_email_available.set(false);
+            revalidate(info);
+        })
+    };
+
+    let onvalidated_email = {
+        let is_email_available = is_email_available.clone();
+        Callback::from(move |result: AsyncTextBoxValidationResult| {
+            is_email_available.set(result == AsyncTextBoxValidationResult::Ok);
+        })
+    };
+
+    let oninput_password = {
+        let register_info = register_info.clone();
+        let revalidate = revalidate.clone();
         Callback::from(move |event: InputEvent| {
             let input: HtmlInputElement = event.target_unchecked_into();
             let mut info = (*register_info).clone();
-            info.username = input.value();
-            is_username_error.set(!validate_user(&info));
-            is_password_error.set(!validate_password(&info));
-            is_email_error.set(!validate_email(&info));
-            is_button_enabled.set(validate_info(&info));
-            register_info.set(info);
+            info.password = input.value();
+            revalidate(info);
         })
     };
 
-    let oninput_email = {
+    let oninput_password_confirm = {
         let register_info = register_info.clone();
-        let is_button_enabled = is_button_enabled.clone();
-        let is_username_error = is_username_error.clone();
-        let is_password_error = is_password_error.clone();
-        let is_email_error = is_email_error.clone();
+        let revalidate = revalidate.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let mut info = (*register_info).clone();
+            info.password_confirm = input.value();
+            revalidate(info);
+        })
+    };
 
+    let oninput_invite_code = {
+        let register_info = register_info.clone();
+        let registration_failure = registration_failure.clone();
         Callback::from(move |event: InputEvent| {
             let input: HtmlInputElement = event.target_unchecked_into();
             let mut info = (*register_info).clone();
-            info.email = input.value();
-            is_username_error.set(!validate_user(&info));
-            is_password_error.set(!validate_password(&info));
-            is_email_error.set(!validate_email(&info));
-            is_button_enabled.set(validate_info(&info));
+            info.invite_code = input.value().trim().to_string();
+            if *registration_failure == Some(RegistrationFailure::InvalidInvite) {
+                registration_failure.set(None);
+            }
             register_info.set(info);
         })
     };
 
-    let widget_id = use_state(|| String::new());
-
-    let onsubmit = {
+    let onchange_terms = {
         let register_info = register_info.clone();
-        let widget_id = widget_id.clone();
-        Callback::from(move |event: MouseEvent| {
-            event.prevent_default();
-            let widget_id = (*widget_id).clone();
-            #[allow(unused_unsafe)]
-            let hcaptcha_response = unsafe { get_hcaptcha_response(widget_id) };
+        let terms_version = terms.version.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
             let mut info = (*register_info).clone();
-            info.hcaptcha_response = hcaptcha_response;
+            info.accepted_terms_version = input.checked().then(|| terms_version.clone());
             register_info.set(info);
-            perform_registration.run();
         })
     };
 
-    let oninput_password = {
+    let oninput_timezone = {
         let register_info = register_info.clone();
-        let is_button_enabled = is_button_enabled.clone();
-        let is_username_error = is_username_error.clone();
-        let is_password_error = is_password_error.clone();
-        let is_email_error = is_email_error.clone();
-
         Callback::from(move |event: InputEvent| {
             let input: HtmlInputElement = event.target_unchecked_into();
             let mut info = (*register_info).clone();
-            info.password = input.value();
-            is_username_error.set(!validate_user(&info));
-            is_password_error.set(!validate_password(&info));
-            is_email_error.set(!validate_email(&info));
-            is_button_enabled.set(validate_info(&info));
+            info.timezone = input.value();
+            register_info.set(info);
+        })
+    };
+
+    let onchange_sleep_goal = {
+        let register_info = register_info.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            let mut info = (*register_info).clone();
+            info.sleep_goal_minutes = select.value().parse().ok();
             register_info.set(info);
         })
     };
@@ -244,57 +594,235 @@ pub fn register_inner() -> HtmlResult {
     let get_widget_id_cb = {
         let widget_id = widget_id.clone();
         Callback::from(move |new_id: String| {
-            widget_id.set(new_id);
+            widget_id.set(Some(new_id));
+        })
+    };
+
+    let is_step_valid = match *step {
+        RegisterStep::Account => {
+            *is_button_enabled
+                && *is_username_available
+                && *is_email_available
+                && (!prereqs.invite_required || !register_info.invite_code.is_empty())
+        }
+        RegisterStep::Terms => register_info.accepted_terms_version.is_some(),
+        RegisterStep::Preferences => true,
+        RegisterStep::Captcha => widget_id.is_some() && !*is_registering,
+    };
+
+    let onsubmit = {
+        let register_info = register_info.clone();
+        let widget_id = widget_id.clone();
+        let captcha_config = captcha_config.clone();
+        let go_to_step = go_to_step.clone();
+        let current_step = *step;
+        Callback::from(move |event: MouseEvent| {
+            event.prevent_default();
+            if let Some(next_step) = current_step.next() {
+                go_to_step.emit(next_step);
+                return;
+            }
+            let captcha_response = match &*widget_id {
+                Some(widget_id) => captcha::response(&captcha_config, widget_id),
+                None => String::new(),
+            };
+            let mut info = (*register_info).clone();
+            info.captcha_response = captcha_response;
+            *submitted_info.borrow_mut() = info;
+            perform_registration.run();
         })
     };
 
+    let maybe_back_button = match step.previous() {
+        Some(previous_step) => {
+            let onclick = Callback::from(move |_| go_to_step.emit(previous_step));
+            html! {
+                <button type="button" class="mb-2 btn btn-lg btn-secondary rounded-3" {onclick} disabled={*is_registering}>
+                    {"Back"}
+                </button>
+            }
+        }
+        None => html! {},
+    };
+
+    let stepper = RegisterStep::ALL
+        .iter()
+        .map(|each_step| {
+            let classes = classes!(
+                "nav-link",
+                (each_step == &*step).then_some("active"),
+                (each_step.index() > step.index()).then_some("disabled"),
+            );
+            html! {
+                <li class="nav-item">
+                    <span class={classes}>{format!("{}. {}", each_step.index() + 1, each_step.title())}</span>
+                </li>
+            }
+        })
+        .collect::<Html>();
+
+    let has_restored_details = match &*restored_draft {
+        Some(draft) => !draft.username.is_empty() || !draft.email.is_empty(),
+        None => false,
+    };
+    let maybe_restored_alert = if has_restored_details && *step == RegisterStep::Account {
+        html! {
+            <Alert style={Color::Info}>{"We kept what you entered before. For your security, please enter your password again."}</Alert>
+        }
+    } else {
+        html! {}
+    };
+
+    let maybe_error_alert = match &*registration_failure {
+        Some(failure) => html! {
+            <Alert style={Color::Danger}>{failure.message()}</Alert>
+        },
+        None => html! {},
+    };
+
+    let maybe_invite_code_input = if prereqs.invite_required {
+        html! {
+            <>
+                <p class="text-body-secondary">{"Registration on this server is by invitation only."}</p>
+                <FormTextBox id="invite_code" input_type="text" label="Invite code" value={register_info.invite_code.clone()}
+                    oninput={oninput_invite_code} is_invalid={*registration_failure == Some(RegistrationFailure::InvalidInvite)} />
+            </>
+        }
+    } else {
+        html! {}
+    };
+
+    let step_content = match *step {
+        RegisterStep::Account => html! {
+            <>
+                {maybe_restored_alert}
+                {maybe_invite_code_input}
+                <AsyncTextBox id="username" input_type="text" label="Username" prefill={register_info.username.clone()}
+                    oninput={oninput_username} onvalidated={onvalidated_username}
+                    onchange_fn={Callback::from(check_username)} debounce_ms={AVAILABILITY_CHECK_DEBOUNCE_MS} />
+                <AsyncTextBox id="email" input_type="email" label="Email" prefill={register_info.email.clone()}
+                    oninput={oninput_email} onvalidated={onvalidated_email}
+                    onchange_fn={Callback::from(check_email)} debounce_ms={AVAILABILITY_CHECK_DEBOUNCE_MS} />
+                <FormTextBox id="password" input_type="password" label="Password" value={register_info.password.clone()} oninput={oninput_password} is_invalid={*is_password_error} />
+                <PasswordStrengthMeter password={register_info.password.clone()} policy={password_policy} />
+                <FormTextBox id="password_confirm" input_type="password" label="Confirm password" value={register_info.password_confirm.clone()} oninput={oninput_password_confirm} is_invalid={*is_password_confirm_error} />
+            </>
+        },
+        RegisterStep::Terms => html! {
+            <>
+                <p>
+                    {"Please read our "}
+                    <a href={terms.terms_url.clone()} target="_blank" rel="noopener noreferrer">{"terms of service"}</a>
+                    {" and "}
+                    <a href={terms.privacy_url.clone()} target="_blank" rel="noopener noreferrer">{"privacy policy"}</a>
+                    {"."}
+                </p>
+                <div class="form-check mb-3">
+                    <input class="form-check-input" type="checkbox" id="accept_terms"
+                        checked={register_info.accepted_terms_version.is_some()} onchange={onchange_terms} />
+                    <label class="form-check-label" for="accept_terms">
+                        {format!("I accept the terms of service and privacy policy (version {}).", terms.version)}
+                    </label>
+                </div>
+            </>
+        },
+        RegisterStep::Preferences => {
+            let sleep_goal_options = SLEEP_GOAL_CHOICES
+                .iter()
+                .map(|minutes| {
+                    html! {
+                        <option value={minutes.to_string()} selected={register_info.sleep_goal_minutes == Some(*minutes)}>
+                            {format!("{}:{:02} hours", minutes / 60, minutes % 60)}
+                        </option>
+                    }
+                })
+                .collect::<Html>();
+            html! {
+                <>
+                    <p class="text-body-secondary">{"These are optional, and you can change them later."}</p>
+                    <FormTextBox id="timezone" input_type="text" label="Timezone" value={register_info.timezone.clone()} oninput={oninput_timezone} />
+                    <div class="form-floating mb-3">
+                        <select class="form-select" id="sleep_goal" onchange={onchange_sleep_goal}>
+                            <option value="" selected={register_info.sleep_goal_minutes.is_none()}>{"No goal"}</option>
+                            {sleep_goal_options}
+                        </select>
+                        <label for="sleep_goal">{"Nightly sleep goal"}</label>
+                    </div>
+                </>
+            }
+        }
+        RegisterStep::Captcha => html! {
+            <>
+                <p>{format!("You are registering as {} with {}.", register_info.username, register_info.email)}</p>
+                <CaptchaWidget config={captcha_config.clone()} on_ready={get_widget_id_cb} />
+            </>
+        },
+    };
+
+    let submit_text = if step.next().is_some() {
+        "Next"
+    } else {
+        "Register"
+    };
+
     Ok(html! {
         <CenteredBox title={"Registration"} >
+            <ul class="nav nav-pills nav-fill mb-3 small">{stepper}</ul>
+            {maybe_error_alert}
             <form>
-                <FormTextBox id="username" input_type="text" label="Username" value={register_info.username.clone()} oninput={oninput_username} is_invalid={*is_username_error} />
-                <FormTextBox id="email" input_type="email" label="Email" value={register_info.email.clone()} oninput={oninput_email} is_invalid={*is_email_error} />
-                <FormTextBox id="password" input_type="password" label="Password" value={register_info.password.clone()} oninput={oninput_password} is_invalid={*is_password_error} />
-                <HcaptchaContainer sitekey={prereqs.hcaptcha_sitekey} get_widget_id={get_widget_id_cb} />
-                <FormSubmitBtn onclick={onsubmit} enabled={*is_button_enabled}>
-                    <LoadingSpinner show={*is_registering} size={Size::Small} />
-                    { "Register" }
-                </FormSubmitBtn>
+                {step_content}
+                <div class="d-flex gap-2">
+                    {maybe_back_button}
+                    <div class="flex-grow-1">
+                        <FormSubmitBtn onclick={onsubmit} enabled={is_step_valid}>
+                            <LoadingSpinner show={*is_registering} size={Size::Small} />
+                            { submit_text }
+                        </FormSubmitBtn>
+                    </div>
+                </div>
             </form>
+            <OidcProviderButtons />
         <p> {"Or "} <Link<Route> to={Route::Login}>{"login using an existing account"}</Link<Route>>{" instead?"}</p>
 
         </CenteredBox>
     })
 }
 
-#[derive(PartialEq, Clone, Properties)]
-struct HcaptchaContainerProps {
-    sitekey: AttrValue,
-    get_widget_id: Callback<String>,
-}
-
-#[function_component(HcaptchaContainer)]
-fn hcaptcha_container(props: &HcaptchaContainerProps) -> Html {
-    let is_first = use_is_first_mount();
-    let hcaptcha_box_ref = use_node_ref();
-    use_effect_with_deps(
-        |(box_ref, is_first, get_id)| {
-            if *is_first {
-                let div = box_ref
-                    .cast::<HtmlElement>()
-                    .expect("hcaptcha_box_ref not attached to div element");
-                #[allow(unused_unsafe)]
-                let widget_id = unsafe { render_hcaptcha(div) };
-                get_id.emit(widget_id);
+/// Send the registration up to `attempts` times, retrying with a growing delay if the server
+/// can't be reached or can't save it.
+///
+/// Returns the ID of the pending registration.
+async fn register_with_retries(
+    request: api_types::v1::RegistrationRequest,
+    attempts: u32,
+) -> Result<api_types::Snowflake, RegistrationFailure> {
+    let mut delay = REGISTRATION_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let failure = match api::auth_register(request.clone()).await {
+            Ok(api::ResponseType_auth_register::Status200(resp)) => match resp {
+                RegistrationResponse::Ok { id } => return Ok(id),
+                RegistrationResponse::PendingRegistrationExists { id } => return Ok(id),
+                RegistrationResponse::CaptchaFailure { error } => {
+                    log::error!("Captcha Error received: {error}");
+                    return Err(RegistrationFailure::Captcha(error.to_string()));
+                }
+                RegistrationResponse::InvalidInviteCode => {
+                    return Err(RegistrationFailure::InvalidInvite)
+                }
+                RegistrationResponse::DatabaseError => RegistrationFailure::DatabaseError,
+            },
+            Err(e) => {
+                log::error!("Failed to send registration: {e}");
+                RegistrationFailure::Network
             }
-        },
-        (
-            hcaptcha_box_ref.clone(),
-            is_first,
-            props.get_widget_id.clone(),
-        ),
-    );
-
-    html!(
-        <div class="h-captcha" data-sitekey={&props.sitekey} data-theme="dark" ref={hcaptcha_box_ref} />
-    )
+        };
+        if attempt >= attempts {
+            return Err(failure);
+        }
+        log::info!("Registration attempt {attempt} failed with {failure:?}, retrying in {delay:?}");
+        sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
 }
@@ -2,10 +2,22 @@ use std::time::{Duration, SystemTime};
 
 use api_types::v1::DateTimeUtc;
 
-mod time;
+pub mod time;
+pub mod auth_events;
+pub mod captcha;
+pub mod oidc;
+pub mod password_strength;
+pub mod user_agent;
+pub mod webauthn;
 #[wasm_bindgen::prelude::wasm_bindgen]
 extern "C" {
     pub fn get_unix_timestamp() -> f64;
+
+    #[wasm_bindgen(js_name=encodeURIComponent)]
+    pub fn encode_uri_component(text: &str) -> String;
+
+    /// Close the Bootstrap modal with this element ID.
+    pub fn hide_modal(id: &str);
 }
 
 pub fn get_current_time() -> DateTimeUtc {
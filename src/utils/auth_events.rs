@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{BroadcastChannel, MessageEvent, StorageEvent};
+use yew::prelude::*;
+use yew_hooks::{use_event_with_window, use_latest};
+
+use super::get_unix_timestamp;
+
+/// Name of the `BroadcastChannel` that the tabs talk over.
+const CHANNEL_NAME: &str = "oyasumi-auth";
+
+/// Key in `localStorage` used to reach tabs in browsers without `BroadcastChannel`.
+const STORAGE_KEY: &str = "oyasumi-auth-event";
+
+thread_local! {
+    /// The one channel object of this tab, used for sending and for every listener.
+    /// A `BroadcastChannel` doesn't receive its own messages, but other channel objects in the same tab would,
+    /// so sharing it keeps the sending tab from reacting to its own event.
+    static CHANNEL: Option<BroadcastChannel> = BroadcastChannel::new(CHANNEL_NAME).ok();
+}
+
+/// Something that happened to the login state in one tab, which the other tabs should follow.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthEvent {
+    LoggedIn,
+    LoggedOut,
+}
+
+/// What actually gets sent between tabs.
+/// The timestamp makes every message distinct, which the `storage` event needs in order to fire.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct AuthEventMessage {
+    event: AuthEvent,
+    sent_at: f64,
+}
+
+impl AuthEventMessage {
+    fn parse(data: &str) -> Option<AuthEvent> {
+        serde_json::from_str::<AuthEventMessage>(data)
+            .ok()
+            .map(|message| message.event)
+    }
+}
+
+/// Tell all other open tabs about a login or logout in this one.
+///
+/// The sending tab does not receive its own event: the `storage` event never fires in the tab that made the change,
+/// and all listeners in a tab share the channel that sends.
+pub fn broadcast_auth_event(event: AuthEvent) {
+    #[allow(unused_unsafe)]
+    let message = AuthEventMessage {
+        event,
+        sent_at: unsafe { get_unix_timestamp() },
+    };
+    let message = match serde_json::to_string(&message) {
+        Ok(message) => message,
+        Err(_) => return,
+    };
+
+    let was_sent = CHANNEL.with(|channel| match channel {
+        Some(channel) => {
+            if channel.post_message(&JsValue::from_str(&message)).is_err() {
+                log::error!("Failed to broadcast auth event {event:?}");
+            }
+            true
+        }
+        None => false,
+    });
+    if !was_sent {
+        if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+            storage.set_item(STORAGE_KEY, &message).ok();
+        }
+    }
+}
+
+/// Run `on_event` whenever another tab broadcasts an [`AuthEvent`].
+#[hook]
+pub fn use_auth_events(on_event: Callback<AuthEvent>) {
+    let on_event = use_latest(on_event);
+
+    {
+        let on_event = on_event.clone();
+        use_effect_with_deps(
+            move |_| {
+                let channel = CHANNEL.with(|channel| channel.clone());
+                let onmessage = Closure::<dyn Fn(MessageEvent)>::new(move |message: MessageEvent| {
+                    if let Some(event) = message
+                        .data()
+                        .as_string()
+                        .and_then(|data| AuthEventMessage::parse(&data))
+                    {
+                        log::debug!("Received auth event from another tab: {event:?}");
+                        on_event.current().emit(event);
+                    }
+                });
+                if let Some(channel) = &channel {
+                    channel
+                        .add_event_listener_with_callback("message", onmessage.as_ref().unchecked_ref())
+                        .ok();
+                }
+
+                move || {
+                    if let Some(channel) = channel {
+                        channel
+                            .remove_event_listener_with_callback(
+                                "message",
+                                onmessage.as_ref().unchecked_ref(),
+                            )
+                            .ok();
+                    }
+                    drop(onmessage);
+                }
+            },
+            (),
+        );
+    }
+
+    use_event_with_window("storage", move |storage_event: StorageEvent| {
+        if storage_event.key().as_deref() != Some(STORAGE_KEY) {
+            return;
+        }
+        if let Some(event) = storage_event
+            .new_value()
+            .and_then(|data| AuthEventMessage::parse(&data))
+        {
+            log::debug!("Received auth event from another tab: {event:?}");
+            on_event.current().emit(event);
+        }
+    });
+}
@@ -0,0 +1,151 @@
+use api_types::v1::CaptchaConfig;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlElement;
+use yew::NodeRef;
+
+/// The response the stub captcha always gives.
+/// The API accepts it when it is configured with the stub as well.
+pub const STUB_CAPTCHA_RESPONSE: &str = "stub-captcha-response";
+
+/// The widget ID of the stub captcha, which doesn't show anything.
+const STUB_WIDGET_ID: &str = "stub";
+
+/// Bindings to the captcha helpers in `index.html`.
+///
+/// The helpers load the provider's script on first use, so that a blocked script only breaks the captcha,
+/// and not the whole page.
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    async fn captcha_render(
+        provider: String,
+        container: HtmlElement,
+        sitekey: String,
+    ) -> Result<JsValue, JsValue>;
+
+    fn captcha_get_response(provider: String, widget_id: String) -> String;
+
+    fn captcha_reset(provider: String, widget_id: String);
+}
+
+/// The name of the provider in the JS helpers, and its site key.
+/// `None` if the server doesn't use a captcha, or uses the stub, which loads no script.
+fn script_provider(config: &CaptchaConfig) -> Option<(&'static str, &str)> {
+    match config {
+        CaptchaConfig::Hcaptcha { sitekey } => Some(("hcaptcha", sitekey)),
+        CaptchaConfig::Turnstile { sitekey } => Some(("turnstile", sitekey)),
+        CaptchaConfig::None | CaptchaConfig::Stub => None,
+    }
+}
+
+/// Whether the user has to solve something before registering.
+pub fn is_required(config: &CaptchaConfig) -> bool {
+    script_provider(config).is_some()
+}
+
+/// A name for the provider to show the user, e.g. when its script can't be loaded.
+pub fn provider_name(config: &CaptchaConfig) -> &'static str {
+    match config {
+        CaptchaConfig::Hcaptcha { .. } => "hCaptcha",
+        CaptchaConfig::Turnstile { .. } => "Cloudflare Turnstile",
+        CaptchaConfig::None => "no captcha",
+        CaptchaConfig::Stub => "the test captcha",
+    }
+}
+
+/// Load the provider's script if needed, and show the captcha inside `container`.
+///
+/// On success, returns the ID of the widget, to pass to [`response`] and [`reset`].
+pub async fn render(config: &CaptchaConfig, container: &NodeRef) -> Result<String, String> {
+    let (provider, sitekey) = match (config, script_provider(config)) {
+        (_, Some(provider)) => provider,
+        (CaptchaConfig::Stub, None) => return Ok(STUB_WIDGET_ID.to_string()),
+        (_, None) => return Ok(String::new()),
+    };
+    let container = container
+        .cast::<HtmlElement>()
+        .ok_or_else(|| "The captcha has nowhere to be shown".to_string())?;
+    match captcha_render(provider.to_string(), container, sitekey.to_string()).await {
+        Ok(widget_id) => widget_id
+            .as_string()
+            .ok_or_else(|| "Captcha helper did not return a widget ID".to_string()),
+        Err(err) => Err(err.as_string().unwrap_or_else(|| format!("{err:?}"))),
+    }
+}
+
+/// The solution the user has entered, to send along with the registration.
+/// Empty if the captcha has not been solved yet, or if there is none.
+pub fn response(config: &CaptchaConfig, widget_id: &str) -> String {
+    match (config, script_provider(config)) {
+        (_, Some((provider, _))) => {
+            #[allow(unused_unsafe)]
+            unsafe {
+                captcha_get_response(provider.to_string(), widget_id.to_string())
+            }
+        }
+        (CaptchaConfig::Stub, None) => STUB_CAPTCHA_RESPONSE.to_string(),
+        (_, None) => String::new(),
+    }
+}
+
+/// Clear the captcha so the user has to solve a new one.
+pub fn reset(config: &CaptchaConfig, widget_id: &str) {
+    if let Some((provider, _)) = script_provider(config) {
+        #[allow(unused_unsafe)]
+        unsafe {
+            captcha_reset(provider.to_string(), widget_id.to_string())
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+
+    /// Run a future that must not wait for anything, like rendering a captcha without a script.
+    fn now_or_never<F: Future>(future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        match pin!(future).poll(&mut context) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the future waited for something"),
+        }
+    }
+
+    #[test]
+    fn stub_renders_without_a_script_or_container() {
+        assert_eq!(script_provider(&CaptchaConfig::Stub), None);
+        assert!(!is_required(&CaptchaConfig::Stub));
+        assert_eq!(
+            now_or_never(render(&CaptchaConfig::Stub, &NodeRef::default())),
+            Ok(STUB_WIDGET_ID.to_string())
+        );
+    }
+
+    #[test]
+    fn stub_always_gives_the_same_response() {
+        assert_eq!(
+            response(&CaptchaConfig::Stub, STUB_WIDGET_ID),
+            STUB_CAPTCHA_RESPONSE
+        );
+        reset(&CaptchaConfig::Stub, STUB_WIDGET_ID);
+        assert_eq!(
+            response(&CaptchaConfig::Stub, STUB_WIDGET_ID),
+            STUB_CAPTCHA_RESPONSE
+        );
+    }
+
+    #[test]
+    fn no_captcha_gives_an_empty_response() {
+        assert_eq!(
+            now_or_never(render(&CaptchaConfig::None, &NodeRef::default())),
+            Ok(String::new())
+        );
+        assert_eq!(response(&CaptchaConfig::None, ""), "");
+    }
+}
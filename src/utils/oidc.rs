@@ -0,0 +1,99 @@
+use api_types::v1::OidcProvider;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use super::encode_uri_component;
+
+/// Key in `sessionStorage` under which the in-progress login is kept while the user is at the provider.
+const PENDING_LOGIN_KEY: &str = "oidc_pending_login";
+
+/// Path that providers redirect back to; must match [`crate::Route::OidcCallback`].
+const CALLBACK_PATH: &str = "/auth/callback";
+
+#[wasm_bindgen]
+extern "C" {
+    fn random_base64url(length: u32) -> String;
+
+    #[wasm_bindgen(catch)]
+    async fn pkce_challenge(verifier: String) -> Result<JsValue, JsValue>;
+}
+
+/// What we need to remember between redirecting to the provider and handling the callback.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PendingOidcLogin {
+    pub provider: String,
+    pub state: String,
+    pub code_verifier: String,
+    /// Sent to the provider, which puts it into the ID token.
+    /// The API checks that the token it gets for the code carries the same nonce, so it can't be replayed.
+    pub nonce: String,
+}
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+/// The absolute URL of the callback route, as sent to the provider.
+pub fn redirect_uri() -> String {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    format!("{origin}{CALLBACK_PATH}")
+}
+
+/// Build the authorization URL for `provider`, remember the PKCE verifier, and send the browser there.
+pub async fn start_login(provider: &OidcProvider) -> Result<(), String> {
+    #[allow(unused_unsafe)]
+    let (code_verifier, state, nonce) =
+        unsafe { (random_base64url(32), random_base64url(16), random_base64url(16)) };
+    let code_challenge = pkce_challenge(code_verifier.clone())
+        .await
+        .ok()
+        .and_then(|challenge| challenge.as_string())
+        .ok_or_else(|| "This browser cannot compute the login challenge.".to_string())?;
+
+    let pending = PendingOidcLogin {
+        provider: provider.id.clone(),
+        state: state.clone(),
+        code_verifier,
+        nonce: nonce.clone(),
+    };
+    let storage = session_storage().ok_or_else(|| "Session storage is unavailable.".to_string())?;
+    let pending = serde_json::to_string(&pending).map_err(|e| e.to_string())?;
+    storage
+        .set_item(PENDING_LOGIN_KEY, &pending)
+        .map_err(|_| "Could not save the login state.".to_string())?;
+
+    let separator = if provider.authorization_url.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    #[allow(unused_unsafe)]
+    let url = unsafe {
+        format!(
+            "{}{separator}response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
+            provider.authorization_url,
+            encode_uri_component(&provider.client_id),
+            encode_uri_component(&redirect_uri()),
+            encode_uri_component(&provider.scope),
+            encode_uri_component(&state),
+            encode_uri_component(&nonce),
+            encode_uri_component(&code_challenge),
+        )
+    };
+
+    web_sys::window()
+        .ok_or_else(|| "No window to redirect.".to_string())?
+        .location()
+        .set_href(&url)
+        .map_err(|_| "Could not redirect to the provider.".to_string())
+}
+
+/// Take the pending login out of storage, so that a callback can only be handled once.
+pub fn take_pending_login() -> Option<PendingOidcLogin> {
+    let storage = session_storage()?;
+    let pending = storage.get_item(PENDING_LOGIN_KEY).ok()??;
+    storage.remove_item(PENDING_LOGIN_KEY).ok();
+    serde_json::from_str(&pending).ok()
+}
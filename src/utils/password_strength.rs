@@ -0,0 +1,215 @@
+use api_types::v1::PasswordPolicy;
+
+/// A rough, human-readable bucket for a password's estimated entropy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PasswordStrength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+impl PasswordStrength {
+    pub fn from_bits(bits: f64) -> Self {
+        match bits {
+            b if b < 28.0 => Self::VeryWeak,
+            b if b < 36.0 => Self::Weak,
+            b if b < 60.0 => Self::Fair,
+            b if b < 128.0 => Self::Strong,
+            _ => Self::VeryStrong,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VeryWeak => "Very weak",
+            Self::Weak => "Weak",
+            Self::Fair => "Fair",
+            Self::Strong => "Strong",
+            Self::VeryStrong => "Very strong",
+        }
+    }
+}
+
+// The character classes, shared by the entropy estimate and the policy check,
+// so that a character that satisfies a requirement is also counted for the estimate.
+
+fn has_lowercase(password: &str) -> bool {
+    password.chars().any(char::is_lowercase)
+}
+
+fn has_uppercase(password: &str) -> bool {
+    password.chars().any(char::is_uppercase)
+}
+
+fn has_digit(password: &str) -> bool {
+    password.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Anything that isn't a letter or a digit, including spaces.
+fn has_symbol(password: &str) -> bool {
+    password.chars().any(|c| !c.is_alphanumeric())
+}
+
+/// Estimate how many bits of entropy a password has.
+///
+/// This assumes that each character was picked at random from the character classes that appear in the password,
+/// but does not count characters that repeat or continue a run of the previous one (`aaa`, `abc`, `321`),
+/// since those add very little for an attacker to guess.
+pub fn estimate_entropy_bits(password: &str) -> f64 {
+    let mut pool = 0u32;
+    if has_lowercase(password) {
+        pool += 26;
+    }
+    if has_uppercase(password) {
+        pool += 26;
+    }
+    if has_digit(password) {
+        pool += 10;
+    }
+    if has_symbol(password) {
+        pool += 33;
+    }
+    // Letters from other alphabets widen the pool a lot more than the classes above suggest.
+    if password.chars().any(|c| !c.is_ascii()) {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+
+    let mut effective_length = 0u32;
+    let mut previous: Option<char> = None;
+    for c in password.chars() {
+        let is_predictable = match previous {
+            Some(p) => {
+                let step = c as i64 - p as i64;
+                step.abs() <= 1
+            }
+            None => false,
+        };
+        if !is_predictable {
+            effective_length += 1;
+        }
+        previous = Some(c);
+    }
+
+    f64::from(effective_length) * f64::from(pool).log2()
+}
+
+/// List what the password is missing to satisfy the policy, as sentences to show the user.
+/// An empty list means the password is acceptable.
+pub fn unmet_requirements(password: &str, policy: &PasswordPolicy) -> Vec<String> {
+    let mut missing = Vec::new();
+    let length = password.chars().count();
+    if length < policy.min_length as usize {
+        missing.push(format!(
+            "Use at least {} characters ({} more).",
+            policy.min_length,
+            policy.min_length as usize - length
+        ));
+    }
+    if policy.require_lowercase && !has_lowercase(password) {
+        missing.push("Add a lowercase letter.".to_string());
+    }
+    if policy.require_uppercase && !has_uppercase(password) {
+        missing.push("Add an uppercase letter.".to_string());
+    }
+    if policy.require_digit && !has_digit(password) {
+        missing.push("Add a digit.".to_string());
+    }
+    if policy.require_symbol && !has_symbol(password) {
+        missing.push("Add a symbol, like ! or #.".to_string());
+    }
+    if estimate_entropy_bits(password) < f64::from(policy.min_entropy_bits) {
+        missing.push(
+            "Make it harder to guess: avoid repeated characters and sequences like \"abc\" or \"123\"."
+                .to_string(),
+        );
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict_policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 8,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            min_entropy_bits: 0,
+        }
+    }
+
+    #[test]
+    fn empty_password_has_no_entropy() {
+        assert_eq!(estimate_entropy_bits(""), 0.0);
+        assert_eq!(PasswordStrength::from_bits(0.0), PasswordStrength::VeryWeak);
+    }
+
+    #[test]
+    fn repeats_and_sequences_count_once() {
+        let one_lowercase = 26f64.log2();
+        assert_eq!(estimate_entropy_bits("a"), one_lowercase);
+        assert_eq!(estimate_entropy_bits("aaaa"), one_lowercase);
+        assert_eq!(estimate_entropy_bits("abcd"), one_lowercase);
+        assert_eq!(estimate_entropy_bits("dcba"), one_lowercase);
+    }
+
+    #[test]
+    fn every_class_widens_the_pool() {
+        // 26 lowercase + 26 uppercase + 10 digits + 33 symbols
+        assert_eq!(estimate_entropy_bits("a1B!"), 4.0 * 95f64.log2());
+    }
+
+    #[test]
+    fn non_ascii_letters_count_for_their_class() {
+        // A lowercase letter, and from outside ASCII.
+        assert_eq!(estimate_entropy_bits("é"), 126f64.log2());
+        let policy = PasswordPolicy {
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            ..strict_policy()
+        };
+        assert!(unmet_requirements("ééxyzqwp", &policy).is_empty());
+    }
+
+    #[test]
+    fn strength_buckets() {
+        assert_eq!(PasswordStrength::from_bits(27.9), PasswordStrength::VeryWeak);
+        assert_eq!(PasswordStrength::from_bits(28.0), PasswordStrength::Weak);
+        assert_eq!(PasswordStrength::from_bits(36.0), PasswordStrength::Fair);
+        assert_eq!(PasswordStrength::from_bits(60.0), PasswordStrength::Strong);
+        assert_eq!(PasswordStrength::from_bits(128.0), PasswordStrength::VeryStrong);
+    }
+
+    #[test]
+    fn lists_every_unmet_requirement() {
+        assert_eq!(
+            unmet_requirements("short", &strict_policy()),
+            vec![
+                "Use at least 8 characters (3 more).".to_string(),
+                "Add an uppercase letter.".to_string(),
+                "Add a digit.".to_string(),
+                "Add a symbol, like ! or #.".to_string(),
+            ]
+        );
+        assert!(unmet_requirements("Sh0rt-but-fine", &strict_policy()).is_empty());
+    }
+
+    #[test]
+    fn predictable_passwords_fail_the_entropy_requirement() {
+        let policy = PasswordPolicy {
+            min_entropy_bits: 40,
+            ..strict_policy()
+        };
+        assert_eq!(unmet_requirements("Aaaaaaa1!", &policy).len(), 1);
+        assert!(unmet_requirements("Tr0ub4dor&3x", &policy).is_empty());
+    }
+}
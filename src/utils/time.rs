@@ -0,0 +1,85 @@
+use std::time::{Duration, SystemTime};
+
+use api_types::v1::DateTimeUtc;
+use chrono::NaiveDate;
+use wasm_bindgen::prelude::*;
+
+use super::get_current_time;
+
+#[wasm_bindgen]
+extern "C" {
+    fn get_browser_timezone() -> String;
+
+    fn local_datetime_to_unix(value: &str) -> f64;
+
+    fn unix_to_local_datetime(seconds: f64) -> String;
+}
+
+/// Format a number of seconds as `h:mm:ss`, like a stopwatch.
+pub fn format_seconds_hms(total_seconds: i64) -> String {
+    let minutes = total_seconds / 60;
+    let hours = minutes / 60;
+    let minutes = minutes % 60;
+    let seconds = total_seconds % 60;
+    format!("{hours}:{minutes:02}:{seconds:02}")
+}
+
+/// The IANA name of the timezone the browser is set to, like `Europe/Berlin`.
+/// Empty if the browser doesn't tell.
+pub fn browser_timezone() -> String {
+    #[allow(unused_unsafe)]
+    unsafe {
+        get_browser_timezone()
+    }
+}
+
+/// Format a time for a `datetime-local` input, in the browser's timezone (`2023-05-01T22:30`).
+pub fn to_local_input_value(time: DateTimeUtc) -> String {
+    #[allow(unused_unsafe)]
+    unsafe {
+        unix_to_local_datetime(time.timestamp() as f64)
+    }
+}
+
+/// Read the value of a `datetime-local` input, which is in the browser's timezone.
+/// Returns `None` if it is empty or invalid.
+pub fn from_local_input_value(value: &str) -> Option<DateTimeUtc> {
+    if value.is_empty() {
+        return None;
+    }
+    #[allow(unused_unsafe)]
+    let seconds = unsafe { local_datetime_to_unix(value) };
+    from_unix_seconds(seconds)
+}
+
+fn from_unix_seconds(seconds: f64) -> Option<DateTimeUtc> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some(DateTimeUtc::from(
+        SystemTime::UNIX_EPOCH + Duration::from_secs_f64(seconds),
+    ))
+}
+
+/// Today's date in the browser's timezone.
+pub fn local_today() -> NaiveDate {
+    let now = get_current_time();
+    #[allow(unused_unsafe)]
+    let datetime = unsafe { unix_to_local_datetime(now.timestamp() as f64) };
+    datetime
+        .get(..10)
+        .and_then(|date| date.parse().ok())
+        .unwrap_or_else(|| now.date_naive())
+}
+
+/// The moment a date starts in the browser's timezone.
+pub fn local_day_start(date: NaiveDate) -> Option<DateTimeUtc> {
+    from_local_input_value(&format!("{date}T00:00"))
+}
+
+/// The moment a date is over in the browser's timezone, i.e. the start of the next day.
+pub fn local_day_end(date: NaiveDate) -> Option<DateTimeUtc> {
+    #[allow(unused_unsafe)]
+    let last_minute = unsafe { local_datetime_to_unix(&format!("{date}T23:59")) };
+    from_unix_seconds(last_minute + 60.0)
+}
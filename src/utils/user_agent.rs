@@ -0,0 +1,118 @@
+/// Browsers to look for in a user agent, in order: many of them also claim to be Chrome or Safari.
+const BROWSERS: [(&str, &str); 9] = [
+    ("Edg/", "Edge"),
+    ("EdgiOS/", "Edge"),
+    ("OPR/", "Opera"),
+    ("SamsungBrowser/", "Samsung Internet"),
+    ("Firefox/", "Firefox"),
+    ("FxiOS/", "Firefox"),
+    ("CriOS/", "Chrome"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+];
+
+/// Operating systems to look for in a user agent, in order: Android claims to be Linux, and iOS claims to be macOS.
+const OPERATING_SYSTEMS: [(&str, &str); 8] = [
+    ("Windows", "Windows"),
+    ("Android", "Android"),
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("CrOS", "ChromeOS"),
+    ("Mac OS X", "macOS"),
+    ("Macintosh", "macOS"),
+    ("Linux", "Linux"),
+];
+
+/// Turn a user agent string into a short name for the device, like "Firefox on Windows".
+///
+/// This only recognizes the common browsers; anything else is described by the first product in the string (e.g. "curl").
+pub fn describe_user_agent(user_agent: &str) -> String {
+    let browser = BROWSERS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+    let os = OPERATING_SYSTEMS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+
+    match (browser, os) {
+        (Some(browser), Some(os)) => format!("{browser} on {os}"),
+        (Some(browser), None) => browser.to_string(),
+        (None, Some(os)) => format!("Unknown browser on {os}"),
+        (None, None) => match user_agent.split(['/', ' ']).next() {
+            Some(product) if !product.is_empty() => product.to_string(),
+            _ => "Unknown device".to_string(),
+        },
+    }
+}
+
+/// Like [`describe_user_agent`], for when the user agent may not be known.
+pub fn describe_device(user_agent: Option<&str>) -> String {
+    match user_agent {
+        Some(user_agent) => describe_user_agent(user_agent),
+        None => "Unknown device".to_string(),
+    }
+}
+
+/// Where a request came from, like "Berlin, Germany (192.0.2.1)", from whatever the API knows about it.
+pub fn describe_location(location: Option<&str>, ip_address: Option<&str>) -> String {
+    match (location, ip_address) {
+        (Some(location), Some(ip_address)) => format!("{location} ({ip_address})"),
+        (Some(location), None) => location.to_string(),
+        (None, Some(ip_address)) => ip_address.to_string(),
+        (None, None) => "Unknown location".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_common_browsers() {
+        let cases = [
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+                "Chrome on Windows",
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0",
+                "Edge on Windows",
+            ),
+            (
+                "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0",
+                "Firefox on Linux",
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Mobile/15E148 Safari/604.1",
+                "Safari on iOS",
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+                "Chrome on Android",
+            ),
+        ];
+        for (user_agent, expected) in cases {
+            assert_eq!(describe_user_agent(user_agent), expected, "{user_agent}");
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_first_product() {
+        assert_eq!(describe_user_agent("curl/8.4.0"), "curl");
+        assert_eq!(describe_user_agent(""), "Unknown device");
+        assert_eq!(describe_device(None), "Unknown device");
+    }
+
+    #[test]
+    fn describes_whatever_location_is_known() {
+        assert_eq!(
+            describe_location(Some("Berlin, Germany"), Some("192.0.2.1")),
+            "Berlin, Germany (192.0.2.1)"
+        );
+        assert_eq!(describe_location(Some("Berlin, Germany"), None), "Berlin, Germany");
+        assert_eq!(describe_location(None, Some("192.0.2.1")), "192.0.2.1");
+        assert_eq!(describe_location(None, None), "Unknown location");
+    }
+}
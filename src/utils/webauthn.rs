@@ -0,0 +1,52 @@
+use wasm_bindgen::prelude::*;
+
+/// Bindings to the WebAuthn helpers in `index.html`.
+///
+/// The helpers take the options sent by the API as a JSON string,
+/// convert the base64url-encoded fields into the buffers that `navigator.credentials` expects,
+/// and encode the resulting credential back into a JSON string for the API.
+#[wasm_bindgen]
+extern "C" {
+    fn passkeys_supported() -> bool;
+
+    #[wasm_bindgen(catch)]
+    async fn passkey_create(options_json: String) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    async fn passkey_get(options_json: String) -> Result<JsValue, JsValue>;
+}
+
+/// Whether this browser can use passkeys at all.
+pub fn are_passkeys_supported() -> bool {
+    #[allow(unused_unsafe)]
+    unsafe {
+        passkeys_supported()
+    }
+}
+
+/// Ask the browser to create a new passkey with `navigator.credentials.create`.
+///
+/// On success, returns the attestation as a JSON string.
+/// On failure (including the user cancelling the prompt), returns a description of the error.
+pub async fn create_passkey(options_json: String) -> Result<String, String> {
+    let result = passkey_create(options_json).await;
+    into_credential_json(result)
+}
+
+/// Ask the browser to sign a challenge with an existing passkey with `navigator.credentials.get`.
+///
+/// On success, returns the assertion as a JSON string.
+/// On failure (including the user cancelling the prompt), returns a description of the error.
+pub async fn get_passkey(options_json: String) -> Result<String, String> {
+    let result = passkey_get(options_json).await;
+    into_credential_json(result)
+}
+
+fn into_credential_json(result: Result<JsValue, JsValue>) -> Result<String, String> {
+    match result {
+        Ok(value) => value
+            .as_string()
+            .ok_or_else(|| "Passkey helper did not return a string".to_string()),
+        Err(err) => Err(err.as_string().unwrap_or_else(|| format!("{err:?}"))),
+    }
+}